@@ -0,0 +1,73 @@
+//! Search latency against catalogs of growing size. Catalog words are made of syllables that
+//! never spell the query words, so each query matches the same few foods at every size.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use diet_core::models::{BasicFood, Database, Visibility};
+use diet_core::search::search_foods;
+
+const SIZES: [usize; 3] = [1_000, 10_000, 50_000];
+const QUERIES: [&str; 4] = ["chicken", "chiken brest", "creme", "oat"];
+
+const SYLLABLES: [&str; 16] = ["ba", "do", "fu", "li", "mo", "na", "pe", "sa", "ti", "vo", "zu", "ga", "lo", "wi", "ne", "ro"];
+const REAL_FOODS: [(&str, &str); 5] = [
+    ("Chicken breast", "poultry"),
+    ("Chicken thigh", "poultry"),
+    ("Crème brûlée", "dessert"),
+    ("Rolled oats", "breakfast"),
+    ("Oat milk", "drink"),
+];
+
+fn food(name: &str, keyword: &str) -> BasicFood {
+    BasicFood {
+        id: BasicFood::new_id(),
+        name: name.to_string(),
+        keywords: vec![keyword.to_string()],
+        calories_per_serving: 100.0,
+        macros: None,
+        fluid_ml: None,
+        version: 1,
+        brand: None,
+        description: None,
+        notes: String::new(),
+        category: None,
+        tags: Vec::new(),
+        allergens: Vec::new(),
+        owner: String::new(),
+        visibility: Visibility::Shared,
+    }
+}
+
+/// A catalog of `size` foods: a few real ones among generated names like "Bafumo Tilo".
+fn catalog(size: usize) -> Database {
+    let mut db = Database::default();
+    let mut seed: u64 = 42;
+    let mut next = |n: usize| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) as usize % n
+    };
+    let mut word = || (0..2 + next(3)).map(|_| SYLLABLES[next(SYLLABLES.len())]).collect::<String>();
+    for (name, keyword) in REAL_FOODS {
+        db.save_basic_food(food(name, keyword));
+    }
+    for _ in REAL_FOODS.len()..size {
+        db.save_basic_food(food(&format!("{} {}", word(), word()), &word()));
+    }
+    db.take_command();
+    db
+}
+
+fn bench_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("search_foods");
+    for size in SIZES {
+        let db = catalog(size);
+        for query in QUERIES {
+            group.bench_with_input(BenchmarkId::new(query, size), &db, |b, db| {
+                b.iter(|| search_foods(db, "bench", black_box(query), true))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
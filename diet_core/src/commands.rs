@@ -0,0 +1,344 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use serde::{Serialize, Deserialize};
+use crate::models::{
+    BasicFood, Category, CompositeFood, Database, ExerciseEntry, FoodLogEntry, FoodVersion, UserProfile, WaterEntry,
+    WeightEntry,
+};
+
+// Stamps data changes. Shared by all databases, so a reloaded database never repeats an earlier stamp.
+static REVISIONS: AtomicU64 = AtomicU64::new(0);
+
+/// One record as it was before and after an action. `None` means the record did not exist.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Diff<T> {
+    pub before: Option<T>,
+    pub after: Option<T>,
+}
+
+/// An invertible change to a single record of the database.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Change {
+    BasicFood(String, Diff<BasicFood>), // Food id
+    CompositeFood(String, Diff<CompositeFood>), // Food id
+    FoodVersions(String, Diff<Vec<FoodVersion>>), // Food id
+    LogEntry(String, String, Diff<FoodLogEntry>), // User id, entry id
+    Profile(String, Diff<UserProfile>), // User id
+    Category(String, Diff<Category>), // Category id
+    Weight(String, String, Diff<WeightEntry>), // User id, date
+    Exercise(String, String, Diff<ExerciseEntry>), // User id, entry id
+    Water(String, String, Diff<WaterEntry>), // User id, entry id
+}
+
+impl Change {
+    fn key(&self) -> (u8, String, String) {
+        match self {
+            Change::BasicFood(id, _) => (0, id.clone(), String::new()),
+            Change::CompositeFood(id, _) => (1, id.clone(), String::new()),
+            Change::FoodVersions(id, _) => (2, id.clone(), String::new()),
+            Change::LogEntry(user_id, id, _) => (3, user_id.clone(), id.clone()),
+            Change::Profile(id, _) => (4, id.clone(), String::new()),
+            Change::Category(id, _) => (5, id.clone(), String::new()),
+            Change::Weight(user_id, date, _) => (6, user_id.clone(), date.clone()),
+            Change::Exercise(user_id, id, _) => (7, user_id.clone(), id.clone()),
+            Change::Water(user_id, id, _) => (8, user_id.clone(), id.clone()),
+        }
+    }
+
+    fn is_noop(&self) -> bool {
+        match self {
+            Change::BasicFood(_, d) => d.before == d.after,
+            Change::CompositeFood(_, d) => d.before == d.after,
+            Change::FoodVersions(_, d) => d.before == d.after,
+            Change::LogEntry(_, _, d) => d.before == d.after,
+            Change::Profile(_, d) => d.before == d.after,
+            Change::Category(_, d) => d.before == d.after,
+            Change::Weight(_, _, d) => d.before == d.after,
+            Change::Exercise(_, _, d) => d.before == d.after,
+            Change::Water(_, _, d) => d.before == d.after,
+        }
+    }
+
+    /// Folds a later change of the same record into this one, keeping the original `before`.
+    /// Returns the later change back if it concerns a different record.
+    fn absorb(&mut self, later: Change) -> Option<Change> {
+        match (self, later) {
+            (Change::BasicFood(a, d), Change::BasicFood(b, l)) if *a == b => d.after = l.after,
+            (Change::CompositeFood(a, d), Change::CompositeFood(b, l)) if *a == b => d.after = l.after,
+            (Change::FoodVersions(a, d), Change::FoodVersions(b, l)) if *a == b => d.after = l.after,
+            (Change::LogEntry(u, a, d), Change::LogEntry(v, b, l)) if *u == v && *a == b => d.after = l.after,
+            (Change::Profile(a, d), Change::Profile(b, l)) if *a == b => d.after = l.after,
+            (Change::Category(a, d), Change::Category(b, l)) if *a == b => d.after = l.after,
+            (Change::Weight(u, a, d), Change::Weight(v, b, l)) if *u == v && *a == b => d.after = l.after,
+            (Change::Exercise(u, a, d), Change::Exercise(v, b, l)) if *u == v && *a == b => d.after = l.after,
+            (Change::Water(u, a, d), Change::Water(v, b, l)) if *u == v && *a == b => d.after = l.after,
+            (_, later) => return Some(later),
+        }
+        None
+    }
+}
+
+/// A named user action made of one or more record changes, e.g. "Log 'Apple'".
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Command {
+    pub label: String,
+    pub changes: Vec<Change>,
+}
+
+impl Command {
+    /// Adds a change, merging it with an earlier change of the same record.
+    fn record(&mut self, change: Change) {
+        let mut change = Some(change);
+        for existing in &mut self.changes {
+            change = existing.absorb(change.take().unwrap());
+            if change.is_none() {
+                return;
+            }
+        }
+        self.changes.extend(change);
+    }
+
+    /// True if `later` changes exactly the same records, so the two can be undone as one step
+    /// (e.g. the frames of a slider drag).
+    pub fn same_records(&self, later: &Command) -> bool {
+        let keys = |command: &Command| -> Vec<(u8, String, String)> {
+            command.changes.iter().map(Change::key).collect()
+        };
+        self.label == later.label && keys(self) == keys(later)
+    }
+
+    pub fn merge(&mut self, later: Command) {
+        for change in later.changes {
+            self.record(change);
+        }
+        self.changes.retain(|change| !change.is_noop());
+    }
+}
+
+/// Changes made since the last call to [`Database::take_command`].
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    label: Option<String>,
+    changes: Vec<Change>,
+}
+
+impl Database {
+    /// Names the action being recorded. The outermost action wins, so a copy that saves
+    /// several foods is still labelled as the copy.
+    pub(crate) fn begin_action(&mut self, label: String) {
+        self.journal.label.get_or_insert(label);
+    }
+
+    /// Everything changed since the last call, as one undoable command.
+    pub fn take_command(&mut self) -> Option<Command> {
+        let journal = std::mem::take(&mut self.journal);
+        let mut command = Command {
+            label: journal.label.unwrap_or_else(|| "Change".to_string()),
+            changes: Vec::new(),
+        };
+        command.merge(Command { label: String::new(), changes: journal.changes });
+        (!command.changes.is_empty()).then_some(command)
+    }
+
+    /// Applies the command's changes backwards (undo) or forwards (redo). Nothing is recorded.
+    pub fn apply_command(&mut self, command: &Command, undo: bool) {
+        if undo {
+            for change in command.changes.iter().rev() {
+                self.apply_change(change, true);
+            }
+        } else {
+            for change in &command.changes {
+                self.apply_change(change, false);
+            }
+        }
+    }
+
+    /// True if every record the command touches is still in the state the command left it in
+    /// (or, for redo, the state it started from). Anything else means the data was changed
+    /// since, by another user or outside the app, and applying the command would clobber it.
+    pub fn can_apply_command(&self, command: &Command, undo: bool) -> bool {
+        fn expected<T>(diff: &Diff<T>, undo: bool) -> &Option<T> {
+            if undo { &diff.after } else { &diff.before }
+        }
+        command.changes.iter().all(|change| match change {
+            Change::BasicFood(id, diff) => self.basic_foods.get(id) == expected(diff, undo).as_ref(),
+            Change::CompositeFood(id, diff) => self.composite_foods.get(id) == expected(diff, undo).as_ref(),
+            Change::FoodVersions(id, diff) => self.food_versions.get(id) == expected(diff, undo).as_ref(),
+            Change::LogEntry(user_id, id, diff) => self.find_log_entry(user_id, id) == expected(diff, undo).as_ref(),
+            Change::Profile(user_id, diff) => {
+                self.user_by_id(user_id).map(|user| &user.profile) == expected(diff, undo).as_ref()
+            }
+            Change::Category(id, diff) => self.categories.get(id) == expected(diff, undo).as_ref(),
+            Change::Weight(user_id, date, diff) => self.weight_entry(user_id, date) == expected(diff, undo).as_ref(),
+            Change::Exercise(user_id, id, diff) => self.find_exercise_entry(user_id, id) == expected(diff, undo).as_ref(),
+            Change::Water(user_id, id, diff) => self.find_water_entry(user_id, id) == expected(diff, undo).as_ref(),
+        })
+    }
+
+    fn apply_change(&mut self, change: &Change, undo: bool) {
+        fn side<T: Clone>(diff: &Diff<T>, undo: bool) -> Option<T> {
+            if undo { diff.before.clone() } else { diff.after.clone() }
+        }
+        match change {
+            Change::BasicFood(id, diff) => self.write_basic_food(id, side(diff, undo)),
+            Change::CompositeFood(id, diff) => self.write_composite_food(id, side(diff, undo)),
+            Change::FoodVersions(id, diff) => self.write_food_versions(id, side(diff, undo)),
+            Change::LogEntry(user_id, id, diff) => self.write_log_entry(user_id, id, side(diff, undo)),
+            Change::Profile(user_id, diff) => self.write_profile(user_id, side(diff, undo)),
+            Change::Category(id, diff) => self.write_category(id, side(diff, undo)),
+            Change::Weight(user_id, date, diff) => self.write_weight_entry(user_id, date, side(diff, undo)),
+            Change::Exercise(user_id, id, diff) => self.write_exercise_entry(user_id, id, side(diff, undo)),
+            Change::Water(user_id, id, diff) => self.write_water_entry(user_id, id, side(diff, undo)),
+        }
+        self.touch();
+    }
+
+    fn record(&mut self, change: Change) {
+        self.journal.changes.push(change);
+        self.touch();
+    }
+
+    /// Changes whenever a record is written or the database is reloaded, so front ends can keep
+    /// what they computed from the data until then.
+    pub fn data_revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub(crate) fn touch(&mut self) {
+        self.revision = REVISIONS.fetch_add(1, Ordering::Relaxed) + 1;
+    }
+
+    // Recorded writes. Every mutation of the user data goes through these.
+
+    pub(crate) fn set_basic_food(&mut self, id: &str, food: Option<BasicFood>) {
+        let before = self.basic_foods.get(id).cloned();
+        self.write_basic_food(id, food.clone());
+        self.record(Change::BasicFood(id.to_string(), Diff { before, after: food }));
+    }
+
+    pub(crate) fn set_composite_food(&mut self, id: &str, food: Option<CompositeFood>) {
+        let before = self.composite_foods.get(id).cloned();
+        self.write_composite_food(id, food.clone());
+        self.record(Change::CompositeFood(id.to_string(), Diff { before, after: food }));
+    }
+
+    pub(crate) fn set_food_versions(&mut self, id: &str, versions: Option<Vec<FoodVersion>>) {
+        let before = self.food_versions.get(id).cloned();
+        self.write_food_versions(id, versions.clone());
+        self.record(Change::FoodVersions(id.to_string(), Diff { before, after: versions }));
+    }
+
+    pub(crate) fn set_log_entry(&mut self, user_id: &str, entry_id: &str, entry: Option<FoodLogEntry>) {
+        let before = self.find_log_entry(user_id, entry_id).cloned();
+        self.write_log_entry(user_id, entry_id, entry.clone());
+        self.record(Change::LogEntry(user_id.to_string(), entry_id.to_string(), Diff { before, after: entry }));
+    }
+
+    pub(crate) fn set_profile(&mut self, user_id: &str, profile: UserProfile) {
+        let before = self.user_by_id(user_id).map(|user| user.profile.clone());
+        self.write_profile(user_id, Some(profile.clone()));
+        self.record(Change::Profile(user_id.to_string(), Diff { before, after: Some(profile) }));
+    }
+
+    pub(crate) fn set_category(&mut self, id: &str, category: Option<Category>) {
+        let before = self.categories.get(id).cloned();
+        self.write_category(id, category.clone());
+        self.record(Change::Category(id.to_string(), Diff { before, after: category }));
+    }
+
+    pub(crate) fn set_weight_entry(&mut self, user_id: &str, date: &str, entry: Option<WeightEntry>) {
+        let before = self.weight_entry(user_id, date).cloned();
+        self.write_weight_entry(user_id, date, entry.clone());
+        self.record(Change::Weight(user_id.to_string(), date.to_string(), Diff { before, after: entry }));
+    }
+
+    pub(crate) fn set_exercise_entry(&mut self, user_id: &str, entry_id: &str, entry: Option<ExerciseEntry>) {
+        let before = self.find_exercise_entry(user_id, entry_id).cloned();
+        self.write_exercise_entry(user_id, entry_id, entry.clone());
+        self.record(Change::Exercise(user_id.to_string(), entry_id.to_string(), Diff { before, after: entry }));
+    }
+
+    pub(crate) fn set_water_entry(&mut self, user_id: &str, entry_id: &str, entry: Option<WaterEntry>) {
+        let before = self.find_water_entry(user_id, entry_id).cloned();
+        self.write_water_entry(user_id, entry_id, entry.clone());
+        self.record(Change::Water(user_id.to_string(), entry_id.to_string(), Diff { before, after: entry }));
+    }
+
+    // Raw writes, shared by the recorded writes and by undo/redo.
+
+    fn write_basic_food(&mut self, id: &str, food: Option<BasicFood>) {
+        match food {
+            Some(food) => self.basic_foods.insert(id.to_string(), food),
+            None => self.basic_foods.remove(id),
+        };
+        self.reindex_food(id);
+    }
+
+    fn write_composite_food(&mut self, id: &str, food: Option<CompositeFood>) {
+        match food {
+            Some(food) => self.composite_foods.insert(id.to_string(), food),
+            None => self.composite_foods.remove(id),
+        };
+        self.reindex_food(id);
+    }
+
+    fn write_food_versions(&mut self, id: &str, versions: Option<Vec<FoodVersion>>) {
+        match versions {
+            Some(versions) => self.food_versions.insert(id.to_string(), versions),
+            None => self.food_versions.remove(id),
+        };
+    }
+
+    fn write_log_entry(&mut self, user_id: &str, entry_id: &str, entry: Option<FoodLogEntry>) {
+        let Some(entry) = entry else {
+            if let Some(entries) = self.food_logs.get_mut(user_id) {
+                entries.retain(|e| e.id != entry_id);
+            }
+            return;
+        };
+        let entries = self.food_logs.entry(user_id.to_string()).or_default();
+        match entries.iter_mut().find(|e| e.id == entry_id) {
+            Some(existing) => *existing = entry,
+            None => entries.push(entry),
+        }
+    }
+
+    fn write_profile(&mut self, user_id: &str, profile: Option<UserProfile>) {
+        if let (Some(user), Some(profile)) = (self.user_by_id_mut(user_id), profile) {
+            user.profile = profile;
+        }
+    }
+
+    fn write_category(&mut self, id: &str, category: Option<Category>) {
+        match category {
+            Some(category) => self.categories.insert(id.to_string(), category),
+            None => self.categories.remove(id),
+        };
+    }
+
+    fn write_weight_entry(&mut self, user_id: &str, date: &str, entry: Option<WeightEntry>) {
+        let entries = self.weight_logs.entry(user_id.to_string()).or_default();
+        entries.retain(|e| e.date != date);
+        if let Some(entry) = entry {
+            entries.push(entry);
+            entries.sort_by(|a, b| a.date.cmp(&b.date));
+        }
+    }
+
+    fn write_exercise_entry(&mut self, user_id: &str, entry_id: &str, entry: Option<ExerciseEntry>) {
+        let entries = self.exercise_logs.entry(user_id.to_string()).or_default();
+        match (entries.iter_mut().find(|e| e.id == entry_id), entry) {
+            (Some(existing), Some(entry)) => *existing = entry,
+            (None, Some(entry)) => entries.push(entry),
+            (_, None) => entries.retain(|e| e.id != entry_id),
+        }
+    }
+
+    fn write_water_entry(&mut self, user_id: &str, entry_id: &str, entry: Option<WaterEntry>) {
+        let entries = self.water_logs.entry(user_id.to_string()).or_default();
+        match (entries.iter_mut().find(|e| e.id == entry_id), entry) {
+            (Some(existing), Some(entry)) => *existing = entry,
+            (None, Some(entry)) => entries.push(entry),
+            (_, None) => entries.retain(|e| e.id != entry_id),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+//! Diet manager logic shared by every front end: the data model, storage, undo history,
+//! and the services and validation the screens go through.
+
+pub mod models;
+pub mod commands;
+pub mod storage;
+pub mod undo;
+pub mod services;
+pub mod validation;
+pub mod reports;
+pub mod search;
@@ -0,0 +1,1684 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use chrono::{Duration, NaiveDateTime, NaiveTime, Timelike};
+use crate::commands::Journal;
+use crate::search::SearchIndex;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BasicFood {
+    pub id: String, // Generated; never shown to the user
+    pub name: String, // Display name
+    pub keywords: Vec<String>,
+    pub calories_per_serving: f32,
+    #[serde(default)]
+    pub macros: Option<Macros>, // Per serving, when known
+    #[serde(default)]
+    pub fluid_ml: Option<f32>, // Per serving, for beverages
+    #[serde(default = "first_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub brand: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub category: Option<String>, // Category id
+    #[serde(default)]
+    pub tags: Vec<FoodTag>,
+    #[serde(default)]
+    pub allergens: Vec<Allergen>,
+    #[serde(default)]
+    pub owner: String, // user_id of the creator; empty for household foods
+    #[serde(default)]
+    pub visibility: Visibility,
+}
+
+impl BasicFood {
+    pub fn new_id() -> String {
+        uuid::Uuid::new_v4().to_string()
+    }
+
+    /// Name shown in lists, with the brand appended when there is one.
+    pub fn display_name(&self) -> String {
+        match &self.brand {
+            Some(brand) if !brand.is_empty() => format!("{} ({})", self.name, brand),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CompositeFood {
+    pub id: String, // Storage key, scoped to the owner (see Database::food_key)
+    pub name: String,
+    pub keywords: Vec<String>,
+    pub components: Vec<FoodComponent>,
+    #[serde(default = "first_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub recipe_yield: RecipeYield, // How many portions the components make
+    #[serde(default)]
+    pub raw_weight_g: Option<f32>, // Total weight of the ingredients before cooking
+    #[serde(default)]
+    pub category: Option<String>, // Category id
+    #[serde(default)]
+    pub tags: Vec<FoodTag>,
+    #[serde(default)]
+    pub owner: String, // user_id of the creator; empty for household foods
+    #[serde(default)]
+    pub visibility: Visibility,
+}
+
+/// Whether other users can see and log a food.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Visibility {
+    Private,
+    #[default]
+    Shared,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+/// How much a recipe makes. Logged servings of a composite food are portions of this yield.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RecipeYield {
+    Portions(f32),
+    CookedWeight { total_g: f32, portion_g: f32 },
+}
+
+impl Default for RecipeYield {
+    fn default() -> Self {
+        RecipeYield::Portions(1.0)
+    }
+}
+
+impl RecipeYield {
+    pub fn portions(&self) -> f32 {
+        let portions = match self {
+            RecipeYield::Portions(portions) => *portions,
+            RecipeYield::CookedWeight { total_g, portion_g } => {
+                if *portion_g > 0.0 { total_g / portion_g } else { 0.0 }
+            }
+        };
+        if portions > 0.0 { portions } else { 1.0 }
+    }
+}
+
+impl CompositeFood {
+    /// The identifier the owner typed in, without the owner scope.
+    pub fn local_id(&self) -> &str {
+        self.id.rsplit_once('/').map_or(self.id.as_str(), |(_, local)| local)
+    }
+    /// Percentage of the raw ingredient weight lost while cooking, when both weights are known.
+    pub fn cooking_loss_percent(&self) -> Option<f32> {
+        match (&self.recipe_yield, self.raw_weight_g) {
+            (RecipeYield::CookedWeight { total_g, .. }, Some(raw_g)) if raw_g > 0.0 => {
+                Some((raw_g - total_g) / raw_g * 100.0)
+            }
+            _ => None,
+        }
+    }
+
+    /// Scales the recipe up or down. Per-portion nutrition is unchanged because the yield is
+    /// scaled together with the components.
+    pub fn scale(&mut self, factor: f32) {
+        if factor <= 0.0 {
+            return;
+        }
+        for component in &mut self.components {
+            component.servings *= factor;
+        }
+        self.recipe_yield = match self.recipe_yield {
+            RecipeYield::Portions(portions) => RecipeYield::Portions(portions * factor),
+            RecipeYield::CookedWeight { total_g, portion_g } => RecipeYield::CookedWeight {
+                total_g: total_g * factor,
+                portion_g,
+            },
+        };
+        self.raw_weight_g = self.raw_weight_g.map(|raw_g| raw_g * factor);
+    }
+}
+
+/// A node in the food category tree, e.g. "Skim" under "Milk" under "Dairy".
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Category {
+    pub id: String,
+    pub name: String,
+    pub parent: Option<String>, // Parent category id; None for top-level categories
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FoodTag {
+    Vegetarian,
+    Vegan,
+    GlutenFree,
+    DairyFree,
+    HomeMade,
+    Organic,
+}
+
+impl FoodTag {
+    pub const ALL: [FoodTag; 6] = [
+        FoodTag::Vegetarian,
+        FoodTag::Vegan,
+        FoodTag::GlutenFree,
+        FoodTag::DairyFree,
+        FoodTag::HomeMade,
+        FoodTag::Organic,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FoodTag::Vegetarian => "Vegetarian",
+            FoodTag::Vegan => "Vegan",
+            FoodTag::GlutenFree => "Gluten-free",
+            FoodTag::DairyFree => "Dairy-free",
+            FoodTag::HomeMade => "Home-made",
+            FoodTag::Organic => "Organic",
+        }
+    }
+}
+
+/// The fourteen major food allergens that must be declared on food labels.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Allergen {
+    Gluten,
+    Crustaceans,
+    Eggs,
+    Fish,
+    Peanuts,
+    Soy,
+    Milk,
+    TreeNuts,
+    Celery,
+    Mustard,
+    Sesame,
+    Sulphites,
+    Lupin,
+    Molluscs,
+}
+
+impl Allergen {
+    pub const ALL: [Allergen; 14] = [
+        Allergen::Gluten,
+        Allergen::Crustaceans,
+        Allergen::Eggs,
+        Allergen::Fish,
+        Allergen::Peanuts,
+        Allergen::Soy,
+        Allergen::Milk,
+        Allergen::TreeNuts,
+        Allergen::Celery,
+        Allergen::Mustard,
+        Allergen::Sesame,
+        Allergen::Sulphites,
+        Allergen::Lupin,
+        Allergen::Molluscs,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Allergen::Gluten => "Gluten",
+            Allergen::Crustaceans => "Crustaceans",
+            Allergen::Eggs => "Eggs",
+            Allergen::Fish => "Fish",
+            Allergen::Peanuts => "Peanuts",
+            Allergen::Soy => "Soy",
+            Allergen::Milk => "Milk",
+            Allergen::TreeNuts => "Tree nuts",
+            Allergen::Celery => "Celery",
+            Allergen::Mustard => "Mustard",
+            Allergen::Sesame => "Sesame",
+            Allergen::Sulphites => "Sulphites",
+            Allergen::Lupin => "Lupin",
+            Allergen::Molluscs => "Molluscs",
+        }
+    }
+}
+
+/// Why a food is not suitable for a user's declared restrictions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RestrictionConflict {
+    Contains(Allergen),
+    Lacks(FoodTag),   // The food is tagged, but not with a required tag
+    Unknown(FoodTag), // The food has no tags at all, so it may or may not qualify
+}
+
+impl RestrictionConflict {
+    pub fn describe(&self) -> String {
+        match self {
+            RestrictionConflict::Contains(allergen) => format!("contains {}", allergen.label()),
+            RestrictionConflict::Lacks(tag) => format!("is not {}", tag.label()),
+            RestrictionConflict::Unknown(tag) => format!("is not marked {}", tag.label()),
+        }
+    }
+
+    /// Whether a Block policy refuses the food. Untagged foods are only ever warned about.
+    pub fn blocks(&self) -> bool {
+        !matches!(self, RestrictionConflict::Unknown(_))
+    }
+}
+
+/// Nutrition of a food version that has since been superseded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FoodVersion {
+    pub version: u32,
+    pub calories_per_serving: f32,
+    #[serde(default)]
+    pub macros: Option<Macros>, // Per serving
+    #[serde(default)]
+    pub fluid_ml: Option<f32>, // Per serving
+    #[serde(default)]
+    pub full_nutrition: bool, // False for versions archived when only calories were kept
+    pub replaced_on: String, // ISO 8601 date the version stopped being current
+}
+
+/// What a food version pins for the log entries logged against it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Nutrition {
+    calories: f32,
+    macros: Option<Macros>,
+    fluid_ml: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FoodComponent {
+    pub food_id: String,
+    pub servings: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FoodLogEntry {
+    #[serde(default)]
+    pub id: String, // Stable identifier used to edit or delete this exact entry
+    pub date: String, // ISO 8601 date format (e.g., "2023-10-01")
+    pub food_id: String,
+    pub servings: f32,
+    pub user_id: String, // Add user_id to associate with a user
+    #[serde(default)]
+    pub food_version: Option<u32>, // Version of the food in force when logged; None follows the current one
+    #[serde(default)]
+    pub meal: MealType,
+    #[serde(default)]
+    pub time: Option<String>, // Local time of day, "HH:MM"
+    #[serde(default)]
+    pub note: Option<String>, // Free text, e.g. "ate out"
+    #[serde(default)]
+    pub photo_path: Option<String>, // Local path of a photo of the meal
+    #[serde(default)]
+    pub hunger: Option<u8>, // Hunger before eating, 1 (not hungry) to 5 (starving)
+    #[serde(default)]
+    pub mood: Option<u8>, // How the user felt afterwards, 1 (bad) to 5 (great)
+    #[serde(default)]
+    pub quick_add: Option<QuickAdd>, // Set for raw calorie entries; food_id is then empty
+}
+
+/// Calories (and optionally macros) logged directly, without creating a food.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QuickAdd {
+    pub label: String,
+    pub calories: f32,
+    #[serde(default)]
+    pub macros: Option<Macros>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Macros {
+    pub protein_g: f32,
+    pub carbs_g: f32,
+    pub fat_g: f32,
+}
+
+impl Macros {
+    pub fn scaled(&self, factor: f32) -> Macros {
+        Macros {
+            protein_g: self.protein_g * factor,
+            carbs_g: self.carbs_g * factor,
+            fat_g: self.fat_g * factor,
+        }
+    }
+
+    pub fn add(&mut self, other: &Macros) {
+        self.protein_g += other.protein_g;
+        self.carbs_g += other.carbs_g;
+        self.fat_g += other.fat_g;
+    }
+
+    /// Share of the energy from protein, carbs and fat (4, 4 and 9 kcal per gram), as
+    /// percentages. None when there are no macros.
+    pub fn energy_split(&self) -> Option<(f32, f32, f32)> {
+        let protein = self.protein_g * 4.0;
+        let carbs = self.carbs_g * 4.0;
+        let fat = self.fat_g * 9.0;
+        let total = protein + carbs + fat;
+        if total <= 0.0 {
+            return None;
+        }
+        Some((protein / total * 100.0, carbs / total * 100.0, fat / total * 100.0))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum MealType {
+    Breakfast,
+    Lunch,
+    Dinner,
+    #[default]
+    Snack,
+}
+
+impl MealType {
+    pub const ALL: [MealType; 4] = [MealType::Breakfast, MealType::Lunch, MealType::Dinner, MealType::Snack];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MealType::Breakfast => "Breakfast",
+            MealType::Lunch => "Lunch",
+            MealType::Dinner => "Dinner",
+            MealType::Snack => "Snack",
+        }
+    }
+
+    /// Fraction of the daily calorie target budgeted for this meal.
+    pub fn budget_share(&self) -> f32 {
+        match self {
+            MealType::Breakfast => 0.25,
+            MealType::Lunch => 0.35,
+            MealType::Dinner => 0.30,
+            MealType::Snack => 0.10,
+        }
+    }
+
+    /// The meal most likely meant when logging at the given "HH:MM" time.
+    pub fn for_time(time: &str) -> MealType {
+        match chrono::NaiveTime::parse_from_str(time, "%H:%M").map(|t| chrono::Timelike::hour(&t)) {
+            Ok(5..=10) => MealType::Breakfast,
+            Ok(11..=14) => MealType::Lunch,
+            Ok(17..=21) => MealType::Dinner,
+            _ => MealType::Snack,
+        }
+    }
+}
+
+/// Returns true for a valid "HH:MM" time of day.
+pub fn is_valid_time(time: &str) -> bool {
+    chrono::NaiveTime::parse_from_str(time, "%H:%M").is_ok()
+}
+
+fn new_entry_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+impl FoodLogEntry {
+    pub fn new(user_id: &str, date: &str, food_id: &str, servings: f32) -> Self {
+        Self {
+            id: new_entry_id(),
+            date: date.to_string(),
+            food_id: food_id.to_string(),
+            servings,
+            user_id: user_id.to_string(),
+            food_version: None,
+            meal: MealType::default(),
+            time: None,
+            note: None,
+            photo_path: None,
+            hunger: None,
+            mood: None,
+            quick_add: None,
+        }
+    }
+
+    /// A quick-add entry carrying its own calories instead of referencing a food.
+    pub fn quick(user_id: &str, date: &str, label: &str, calories: f32, macros: Option<Macros>) -> Self {
+        let mut entry = Self::new(user_id, date, "", 1.0);
+        entry.quick_add = Some(QuickAdd { label: label.to_string(), calories, macros });
+        entry
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Gender {
+    Male,
+    Female,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ActivityLevel {
+    Sedentary,
+    Light,
+    Moderate,
+    VeryActive,
+    ExtraActive,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum CalorieCalculationMethod {
+    HarrisBenedict,
+    MifflinStJeor,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserProfile {
+    pub gender: Gender,
+    pub height_cm: f32,
+    pub age: u32,
+    pub calorie_method: CalorieCalculationMethod,
+    pub weight_kg: f32,
+    pub activity_level: ActivityLevel,
+    #[serde(default)]
+    pub allergies: Vec<Allergen>,
+    #[serde(default)]
+    pub dietary_requirements: Vec<FoodTag>, // e.g. GlutenFree for a celiac
+    #[serde(default)]
+    pub restriction_policy: RestrictionPolicy,
+    #[serde(default)]
+    pub weight_from_log: bool, // The latest entry of the weight log sets weight_kg
+    #[serde(default)]
+    pub eat_back_exercise: bool, // Calories burned by exercise are added to the day's budget
+    #[serde(default)]
+    pub fasting: Option<FastingSchedule>, // Intermittent fasting, if the user follows it
+}
+
+/// What happens when a user picks a food that conflicts with their restrictions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum RestrictionPolicy {
+    #[default]
+    Warn,
+    Block,
+}
+
+/// A daily eating window for intermittent fasting; the rest of the day is the fast.
+/// A window may run past midnight.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FastingSchedule {
+    pub window_start: String, // Local time of day, "HH:MM"
+    pub window_hours: u32,    // 1 to 23; 8 for 16:8
+}
+
+impl Default for FastingSchedule {
+    fn default() -> Self {
+        Self { window_start: "12:00".to_string(), window_hours: 8 }
+    }
+}
+
+/// Where a user is in their fasting schedule at some moment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FastingPhase {
+    Eating { since: NaiveDateTime, until: NaiveDateTime },
+    Fasting { since: NaiveDateTime, until: NaiveDateTime },
+}
+
+impl FastingSchedule {
+    /// Window lengths of the common schedules: 16:8, 18:6, 20:4 and 14:10.
+    pub const PRESETS: [u32; 4] = [8, 6, 4, 10];
+
+    fn start_minutes(&self) -> u32 {
+        NaiveTime::parse_from_str(&self.window_start, "%H:%M").map_or(0, |t| t.hour() * 60 + t.minute())
+    }
+
+    pub fn window_end(&self) -> String {
+        let end = (self.start_minutes() + self.window_hours * 60) % (24 * 60);
+        format!("{:02}:{:02}", end / 60, end % 60)
+    }
+
+    /// E.g. "16:8, eating 12:00-20:00".
+    pub fn label(&self) -> String {
+        format!("{}:{}, eating {}-{}", 24 - self.window_hours, self.window_hours, self.window_start, self.window_end())
+    }
+
+    /// Whether an "HH:MM" time falls in the eating window. None for an invalid time.
+    pub fn in_window(&self, time: &str) -> Option<bool> {
+        let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+        let minutes = time.hour() * 60 + time.minute();
+        Some((minutes + 24 * 60 - self.start_minutes()) % (24 * 60) < self.window_hours * 60)
+    }
+
+    /// The phase at `now`, with when it started and when it ends.
+    pub fn phase_at(&self, now: NaiveDateTime) -> FastingPhase {
+        let start = self.start_minutes();
+        let window = Duration::hours(self.window_hours as i64);
+        let mut opens = now.date().and_hms_opt(start / 60, start % 60, 0).unwrap_or(now);
+        if now < opens {
+            opens -= Duration::days(1);
+        }
+        let closes = opens + window;
+        if now < closes {
+            FastingPhase::Eating { since: opens, until: closes }
+        } else {
+            FastingPhase::Fasting { since: closes, until: opens + Duration::days(1) }
+        }
+    }
+}
+
+impl UserProfile {
+    /// Daily water goal: 35 ml per kg of body weight.
+    pub fn water_goal_ml(&self) -> f32 {
+        35.0 * self.weight_kg
+    }
+
+    pub fn calculate_target_calories(&self) -> f32 {
+        let bmr = match self.gender {
+            Gender::Male => 88.362 + (13.397 * self.weight_kg) + (4.799 * self.height_cm) - (5.677 * self.age as f32),
+            Gender::Female => 447.593 + (9.247 * self.weight_kg) + (3.098 * self.height_cm) - (4.330 * self.age as f32),
+        };
+        bmr * match self.activity_level {
+            ActivityLevel::Sedentary => 1.2,
+            ActivityLevel::Light => 1.375,
+            ActivityLevel::Moderate => 1.55,
+            ActivityLevel::VeryActive => 1.725,
+            ActivityLevel::ExtraActive => 1.9,
+        }
+    }
+}
+
+/// A kind of exercise, with MET values (energy cost relative to rest) from the Compendium of
+/// Physical Activities.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activity {
+    Walking,
+    Running,
+    Cycling,
+    Swimming,
+    StrengthTraining,
+    Yoga,
+    Dancing,
+    Gardening,
+}
+
+impl Activity {
+    pub const ALL: [Activity; 8] = [
+        Activity::Walking,
+        Activity::Running,
+        Activity::Cycling,
+        Activity::Swimming,
+        Activity::StrengthTraining,
+        Activity::Yoga,
+        Activity::Dancing,
+        Activity::Gardening,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Activity::Walking => "Walking",
+            Activity::Running => "Running",
+            Activity::Cycling => "Cycling",
+            Activity::Swimming => "Swimming",
+            Activity::StrengthTraining => "Strength training",
+            Activity::Yoga => "Yoga",
+            Activity::Dancing => "Dancing",
+            Activity::Gardening => "Gardening",
+        }
+    }
+
+    pub fn met(&self, intensity: Intensity) -> f32 {
+        let (light, moderate, vigorous) = match self {
+            Activity::Walking => (2.8, 3.5, 5.0),
+            Activity::Running => (6.0, 9.8, 11.5),
+            Activity::Cycling => (4.0, 6.8, 10.0),
+            Activity::Swimming => (5.8, 7.0, 9.8),
+            Activity::StrengthTraining => (3.5, 5.0, 6.0),
+            Activity::Yoga => (2.3, 3.0, 4.0),
+            Activity::Dancing => (4.5, 5.5, 7.8),
+            Activity::Gardening => (2.3, 3.8, 5.0),
+        };
+        match intensity {
+            Intensity::Light => light,
+            Intensity::Moderate => moderate,
+            Intensity::Vigorous => vigorous,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Intensity {
+    Light,
+    #[default]
+    Moderate,
+    Vigorous,
+}
+
+impl Intensity {
+    pub const ALL: [Intensity; 3] = [Intensity::Light, Intensity::Moderate, Intensity::Vigorous];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Intensity::Light => "Light",
+            Intensity::Moderate => "Moderate",
+            Intensity::Vigorous => "Vigorous",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExerciseEntry {
+    pub id: String,
+    pub user_id: String,
+    pub date: String, // ISO 8601 date
+    pub activity: Activity,
+    pub intensity: Intensity,
+    pub duration_min: f32,
+    pub calories_burned: f32, // Estimated when logged, from the weight at the time
+}
+
+impl ExerciseEntry {
+    pub fn new(user_id: &str, date: &str, activity: Activity, intensity: Intensity, duration_min: f32) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            date: date.to_string(),
+            activity,
+            intensity,
+            duration_min,
+            calories_burned: 0.0,
+        }
+    }
+
+    /// Calories burned: MET x body weight in kg x hours.
+    pub fn estimate_calories(&self, weight_kg: f32) -> f32 {
+        self.activity.met(self.intensity) * weight_kg * self.duration_min / 60.0
+    }
+}
+
+/// Calories of a day against its budget.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct DailySummary {
+    pub consumed: f32,
+    pub base_target: f32, // From the profile
+    pub burned: f32, // By logged exercise
+    pub target: f32, // The day's budget: the base target, plus the burned calories if eaten back
+    pub remaining: f32, // Negative when over budget
+}
+
+/// Water drunk, logged separately from food.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WaterEntry {
+    pub id: String,
+    pub user_id: String,
+    pub date: String, // ISO 8601 date
+    pub amount_ml: f32,
+    #[serde(default)]
+    pub time: Option<String>, // Local time of day, "HH:MM"
+}
+
+impl WaterEntry {
+    pub fn new(user_id: &str, date: &str, amount_ml: f32) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            date: date.to_string(),
+            amount_ml,
+            time: None,
+        }
+    }
+}
+
+/// Body weight and optional measurements on a date. One entry per user and date.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WeightEntry {
+    pub date: String, // ISO 8601 date
+    pub weight_kg: f32,
+    #[serde(default)]
+    pub waist_cm: Option<f32>,
+    #[serde(default)]
+    pub body_fat_percent: Option<f32>,
+}
+
+impl WeightEntry {
+    pub fn new(date: &str, weight_kg: f32) -> Self {
+        Self { date: date.to_string(), weight_kg, waist_cm: None, body_fat_percent: None }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct User {
+    pub user_id: String,
+    pub username: String,
+    pub password: String, // In a real application, this should be hashed
+    pub profile: UserProfile,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Database {
+    pub users: HashMap<String, User>, // Key: username, Value: User; the other per-user maps use user_id
+    pub basic_foods: HashMap<String, BasicFood>,
+    pub composite_foods: HashMap<String, CompositeFood>,
+    pub food_logs: HashMap<String, Vec<FoodLogEntry>>, // Key: user_id, Value: logs
+    #[serde(default)]
+    pub food_versions: HashMap<String, Vec<FoodVersion>>, // Key: food id, Value: superseded versions
+    #[serde(default)]
+    pub categories: HashMap<String, Category>, // Key: category id
+    #[serde(default)]
+    pub weight_logs: HashMap<String, Vec<WeightEntry>>, // Key: user_id, Value: entries by date
+    #[serde(default)]
+    pub exercise_logs: HashMap<String, Vec<ExerciseEntry>>, // Key: user_id
+    #[serde(default)]
+    pub water_logs: HashMap<String, Vec<WaterEntry>>, // Key: user_id
+    #[serde(skip)]
+    pub current_user: String, // Logged in to this front end; never saved, as each keeps its own session
+    #[serde(skip)]
+    pub(crate) journal: Journal, // Changes not yet handed to the undo manager
+    #[serde(skip)]
+    pub(crate) revision: u64, // Stamped by every write; see data_revision
+    #[serde(skip)]
+    pub(crate) search_index: SearchIndex, // Kept up to date by the food writes
+}
+
+impl Database {
+    /// Calories per serving. For composite foods this is per portion of the recipe yield.
+    pub fn get_food_calories(&self, food_id: &str) -> Option<f32> {
+        if let Some(basic_food) = self.basic_foods.get(food_id) {
+            Some(basic_food.calories_per_serving)
+        } else if let Some(composite_food) = self.composite_foods.get(food_id) {
+            let mut total_calories = 0.0;
+            for component in &composite_food.components {
+                if let Some(calories) = self.get_food_calories(&component.food_id) {
+                    total_calories += calories * component.servings;
+                }
+            }
+            Some(total_calories / composite_food.recipe_yield.portions())
+        } else {
+            None
+        }
+    }
+
+    pub fn add_category(&mut self, name: &str, parent: Option<String>) -> String {
+        self.begin_action(format!("Add category '{}'", name));
+        let id = uuid::Uuid::new_v4().to_string();
+        self.set_category(&id, Some(Category { id: id.clone(), name: name.to_string(), parent }));
+        id
+    }
+
+    /// Removes a category. Its children move up to its parent and foods in it become uncategorized.
+    pub fn delete_category(&mut self, category_id: &str) {
+        let Some(removed) = self.categories.get(category_id).cloned() else {
+            return;
+        };
+        self.begin_action(format!("Delete category '{}'", removed.name));
+        self.set_category(category_id, None);
+
+        let children: Vec<Category> = self.categories.values()
+            .filter(|category| category.parent.as_deref() == Some(category_id))
+            .cloned()
+            .collect();
+        for mut category in children {
+            category.parent = removed.parent.clone();
+            self.set_category(&category.id.clone(), Some(category));
+        }
+        let basic_foods: Vec<BasicFood> = self.basic_foods.values()
+            .filter(|food| food.category.as_deref() == Some(category_id))
+            .cloned()
+            .collect();
+        for mut food in basic_foods {
+            food.category = None;
+            self.set_basic_food(&food.id.clone(), Some(food));
+        }
+        let composite_foods: Vec<CompositeFood> = self.composite_foods.values()
+            .filter(|food| food.category.as_deref() == Some(category_id))
+            .cloned()
+            .collect();
+        for mut food in composite_foods {
+            food.category = None;
+            self.set_composite_food(&food.id.clone(), Some(food));
+        }
+    }
+
+    /// Full path of a category, e.g. "Dairy > Milk > Skim".
+    pub fn category_path(&self, category_id: &str) -> String {
+        let mut names = Vec::new();
+        let mut current = self.categories.get(category_id);
+        while let Some(category) = current {
+            if names.len() > self.categories.len() {
+                break; // Guard against a corrupted parent cycle
+            }
+            names.push(category.name.clone());
+            current = category.parent.as_ref().and_then(|parent| self.categories.get(parent));
+        }
+        names.reverse();
+        names.join(" > ")
+    }
+
+    /// Returns true if `category_id` is `ancestor_id` or lies anywhere below it.
+    pub fn is_in_category(&self, category_id: &str, ancestor_id: &str) -> bool {
+        let mut current = Some(category_id);
+        let mut depth = 0;
+        while let Some(id) = current {
+            if id == ancestor_id {
+                return true;
+            }
+            depth += 1;
+            if depth > self.categories.len() {
+                return false;
+            }
+            current = self.categories.get(id).and_then(|category| category.parent.as_deref());
+        }
+        false
+    }
+
+    /// Categories a food belongs to: its own, plus for composite foods those of all components.
+    pub fn food_categories(&self, food_id: &str) -> Vec<String> {
+        if let Some(food) = self.basic_foods.get(food_id) {
+            food.category.iter().cloned().collect()
+        } else if let Some(food) = self.composite_foods.get(food_id) {
+            let mut categories: Vec<String> = food.category.iter().cloned().collect();
+            for component in &food.components {
+                for category in self.food_categories(&component.food_id) {
+                    if !categories.contains(&category) {
+                        categories.push(category);
+                    }
+                }
+            }
+            categories
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn food_in_category(&self, food_id: &str, category_id: &str) -> bool {
+        self.food_categories(food_id).iter().any(|category| self.is_in_category(category, category_id))
+    }
+
+    /// Tags of a food. A composite food has its own tags plus every tag shared by all of its
+    /// components, so a dish made only of vegan ingredients is vegan.
+    pub fn effective_tags(&self, food_id: &str) -> Vec<FoodTag> {
+        if let Some(food) = self.basic_foods.get(food_id) {
+            food.tags.clone()
+        } else if let Some(food) = self.composite_foods.get(food_id) {
+            let mut tags = food.tags.clone();
+            let mut components = food.components.iter();
+            if let Some(first) = components.next() {
+                let mut shared = self.effective_tags(&first.food_id);
+                for component in components {
+                    let component_tags = self.effective_tags(&component.food_id);
+                    shared.retain(|tag| component_tags.contains(tag));
+                }
+                tags.extend(shared);
+            }
+            tags.sort();
+            tags.dedup();
+            tags
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn user_by_id(&self, user_id: &str) -> Option<&User> {
+        self.users.values().find(|u| u.user_id == user_id)
+    }
+
+    /// Replaces the user's profile. When the weight comes from the weight log it is taken from
+    /// the latest entry; otherwise a changed weight is recorded in the log for today.
+    pub fn update_profile(&mut self, user_id: &str, mut profile: UserProfile) -> Result<(), String> {
+        let Some(user) = self.user_by_id(user_id) else {
+            return Err(format!("User '{}' does not exist.", user_id));
+        };
+        let weight_changed = user.profile.weight_kg != profile.weight_kg;
+        self.begin_action("Update profile".to_string());
+        if profile.weight_from_log {
+            if let Some(latest) = self.latest_weight(user_id) {
+                profile.weight_kg = latest.weight_kg;
+            }
+        } else if weight_changed {
+            let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+            let mut entry = self.weight_entry(user_id, &date).cloned().unwrap_or_else(|| WeightEntry::new(&date, 0.0));
+            entry.weight_kg = profile.weight_kg;
+            self.set_weight_entry(user_id, &date, Some(entry));
+        }
+        self.set_profile(user_id, profile);
+        Ok(())
+    }
+
+    /// Records the weight and measurements of a date, replacing any entry on that date.
+    pub fn log_weight(&mut self, user_id: &str, entry: WeightEntry) {
+        self.begin_action(format!("Log weight {:.1} kg", entry.weight_kg));
+        let date = entry.date.clone();
+        self.set_weight_entry(user_id, &date, Some(entry));
+        self.sync_logged_weight(user_id);
+    }
+
+    pub fn delete_weight_entry(&mut self, user_id: &str, date: &str) -> Option<WeightEntry> {
+        let entry = self.weight_entry(user_id, date)?.clone();
+        self.begin_action(format!("Delete weight of {}", date));
+        self.set_weight_entry(user_id, date, None);
+        self.sync_logged_weight(user_id);
+        Some(entry)
+    }
+
+    /// Copies the latest logged weight into the profile of a user whose weight follows the log.
+    fn sync_logged_weight(&mut self, user_id: &str) {
+        let Some(latest) = self.latest_weight(user_id).map(|entry| entry.weight_kg) else {
+            return;
+        };
+        if let Some(user) = self.user_by_id(user_id) {
+            if user.profile.weight_from_log && user.profile.weight_kg != latest {
+                let mut profile = user.profile.clone();
+                profile.weight_kg = latest;
+                self.set_profile(user_id, profile);
+            }
+        }
+    }
+
+    pub fn latest_weight(&self, user_id: &str) -> Option<&WeightEntry> {
+        self.weight_history(user_id).last()
+    }
+
+    /// The user's recorded weights, oldest first.
+    pub fn weight_history(&self, user_id: &str) -> &[WeightEntry] {
+        self.weight_logs.get(user_id).map_or(&[], |entries| entries.as_slice())
+    }
+
+    /// The user's weight as of a date: the last one recorded on or before it, or their
+    /// profile weight when none was.
+    pub fn weight_on(&self, user_id: &str, date: &str) -> f32 {
+        self.weight_history(user_id).iter()
+            .rev()
+            .find(|entry| entry.date.as_str() <= date)
+            .map(|entry| entry.weight_kg)
+            .or_else(|| self.user_by_id(user_id).map(|user| user.profile.weight_kg))
+            .unwrap_or(0.0)
+    }
+
+    pub fn weight_entry(&self, user_id: &str, date: &str) -> Option<&WeightEntry> {
+        self.weight_history(user_id).iter().find(|entry| entry.date == date)
+    }
+
+    pub fn user_by_id_mut(&mut self, user_id: &str) -> Option<&mut User> {
+        self.users.values_mut().find(|u| u.user_id == user_id)
+    }
+
+    /// Storage key for a user-chosen food identifier. Identifiers are unique per owner, so two
+    /// users can both have a "my_smoothie".
+    pub fn food_key(owner: &str, local_id: &str) -> String {
+        if owner.is_empty() {
+            local_id.to_string()
+        } else {
+            format!("{}/{}", owner, local_id)
+        }
+    }
+
+    pub fn food_owner(&self, food_id: &str) -> Option<&str> {
+        self.food_owner_and_visibility(food_id).map(|(owner, _)| owner)
+    }
+
+    fn food_owner_and_visibility(&self, food_id: &str) -> Option<(&str, Visibility)> {
+        if let Some(food) = self.basic_foods.get(food_id) {
+            Some((&food.owner, food.visibility))
+        } else {
+            self.composite_foods.get(food_id).map(|food| (food.owner.as_str(), food.visibility))
+        }
+    }
+
+    /// Household foods and shared foods are visible to everyone; private ones only to their owner.
+    pub fn food_visible_to(&self, food_id: &str, user_id: &str) -> bool {
+        self.food_owner_and_visibility(food_id).is_some_and(|(owner, visibility)| {
+            owner.is_empty() || owner == user_id || visibility == Visibility::Shared
+        })
+    }
+
+    /// Only the owner may change a food; household foods may be changed by anyone.
+    pub fn food_editable_by(&self, food_id: &str, user_id: &str) -> bool {
+        self.food_owner_and_visibility(food_id)
+            .is_some_and(|(owner, _)| owner.is_empty() || owner == user_id)
+    }
+
+    /// Copies another user's shared composite food into the user's own catalog as a private
+    /// food. Components the user cannot see are copied along with it. Returns the new food id.
+    pub fn copy_composite_food(&mut self, food_id: &str, user_id: &str) -> Result<String, String> {
+        let Some(source) = self.composite_foods.get(food_id) else {
+            return Err(format!("Composite food '{}' does not exist.", food_id));
+        };
+        if !self.food_visible_to(food_id, user_id) {
+            return Err(format!("'{}' is not shared with you.", source.name));
+        }
+        let key = Self::food_key(user_id, source.local_id());
+        if self.food_exists(&key) {
+            return Err(format!("You already have a food with identifier '{}'.", source.local_id()));
+        }
+        self.begin_action(format!("Copy '{}'", source.name));
+
+        Ok(self.copy_food_as(food_id, user_id, key))
+    }
+
+    /// Copies a food into a user's catalog under `key` as a private food, recursively copying
+    /// the components the user cannot see.
+    fn copy_food_as(&mut self, food_id: &str, user_id: &str, key: String) -> String {
+        if let Some(food) = self.basic_foods.get(food_id) {
+            let mut copy = food.clone();
+            copy.id = key.clone();
+            copy.owner = user_id.to_string();
+            copy.visibility = Visibility::Private;
+            copy.version = 1;
+            self.save_basic_food(copy);
+        } else if let Some(food) = self.composite_foods.get(food_id) {
+            let mut copy = food.clone();
+            copy.id = key.clone();
+            copy.owner = user_id.to_string();
+            copy.visibility = Visibility::Private;
+            copy.version = 1;
+            for component in &mut copy.components {
+                if !self.food_visible_to(&component.food_id, user_id) {
+                    let component_key = self.unused_key_for(&component.food_id, user_id);
+                    component.food_id = self.copy_food_as(&component.food_id, user_id, component_key);
+                }
+            }
+            self.save_composite_food(copy);
+        }
+        key
+    }
+
+    fn unused_key_for(&self, food_id: &str, user_id: &str) -> String {
+        let Some(food) = self.composite_foods.get(food_id) else {
+            return BasicFood::new_id();
+        };
+        let mut key = Self::food_key(user_id, food.local_id());
+        let mut suffix = 2;
+        while self.food_exists(&key) {
+            key = Self::food_key(user_id, &format!("{}_{}", food.local_id(), suffix));
+            suffix += 1;
+        }
+        key
+    }
+
+    /// Allergens of a food. Composite foods contain every allergen of their components.
+    pub fn food_allergens(&self, food_id: &str) -> Vec<Allergen> {
+        if let Some(food) = self.basic_foods.get(food_id) {
+            food.allergens.clone()
+        } else if let Some(food) = self.composite_foods.get(food_id) {
+            let mut allergens: Vec<Allergen> = food.components.iter()
+                .flat_map(|component| self.food_allergens(&component.food_id))
+                .collect();
+            allergens.sort();
+            allergens.dedup();
+            allergens
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Ways in which a food conflicts with the allergies and dietary requirements of a user.
+    pub fn restriction_conflicts(&self, user_id: &str, food_id: &str) -> Vec<RestrictionConflict> {
+        let Some(user) = self.user_by_id(user_id) else {
+            return Vec::new();
+        };
+        let mut conflicts: Vec<RestrictionConflict> = self.food_allergens(food_id)
+            .into_iter()
+            .filter(|allergen| user.profile.allergies.contains(allergen))
+            .map(RestrictionConflict::Contains)
+            .collect();
+        let tags = self.effective_tags(food_id);
+        conflicts.extend(user.profile.dietary_requirements.iter()
+            .filter(|tag| !tags.contains(tag))
+            .map(|tag| if tags.is_empty() { RestrictionConflict::Unknown(*tag) } else { RestrictionConflict::Lacks(*tag) }));
+        conflicts
+    }
+
+    /// Display name of any food, falling back to the id for foods that no longer exist.
+    pub fn food_display_name(&self, food_id: &str) -> String {
+        if let Some(food) = self.basic_foods.get(food_id) {
+            food.display_name()
+        } else if let Some(food) = self.composite_foods.get(food_id) {
+            food.name.clone()
+        } else {
+            food_id.to_string()
+        }
+    }
+
+    /// What a log entry is called in lists: the food's display name or the quick-add label.
+    pub fn entry_display_name(&self, entry: &FoodLogEntry) -> String {
+        match &entry.quick_add {
+            Some(quick_add) => format!("{} (quick add)", quick_add.label),
+            None => self.food_display_name(&entry.food_id),
+        }
+    }
+
+    /// Macros per serving. For composite foods this is per portion, counting the components
+    /// whose macros are known; None when none of them are.
+    pub fn get_food_macros(&self, food_id: &str) -> Option<Macros> {
+        if let Some(basic_food) = self.basic_foods.get(food_id) {
+            basic_food.macros
+        } else {
+            let composite_food = self.composite_foods.get(food_id)?;
+            let mut total: Option<Macros> = None;
+            for component in &composite_food.components {
+                if let Some(macros) = self.get_food_macros(&component.food_id) {
+                    total.get_or_insert_with(Macros::default).add(&macros.scaled(component.servings));
+                }
+            }
+            total.map(|macros| macros.scaled(1.0 / composite_food.recipe_yield.portions()))
+        }
+    }
+
+    /// Fluid per serving of a beverage. For composite foods this is per portion; None when no
+    /// component is a beverage.
+    pub fn get_food_fluid(&self, food_id: &str) -> Option<f32> {
+        if let Some(basic_food) = self.basic_foods.get(food_id) {
+            basic_food.fluid_ml
+        } else {
+            let composite_food = self.composite_foods.get(food_id)?;
+            let fluids: Vec<f32> = composite_food.components.iter()
+                .filter_map(|component| Some(self.get_food_fluid(&component.food_id)? * component.servings))
+                .collect();
+            (!fluids.is_empty()).then(|| fluids.iter().sum::<f32>() / composite_food.recipe_yield.portions())
+        }
+    }
+
+    pub fn entry_fluid(&self, entry: &FoodLogEntry) -> f32 {
+        if entry.quick_add.is_some() {
+            return 0.0;
+        }
+        let fluid = match self.archived_version(&entry.food_id, entry.food_version) {
+            Some(archived) if archived.full_nutrition => archived.fluid_ml,
+            _ => self.get_food_fluid(&entry.food_id),
+        };
+        fluid.unwrap_or(0.0) * entry.servings
+    }
+
+    pub fn entry_macros(&self, entry: &FoodLogEntry) -> Option<Macros> {
+        let macros = match &entry.quick_add {
+            Some(quick_add) => quick_add.macros,
+            None => match self.archived_version(&entry.food_id, entry.food_version) {
+                Some(archived) if archived.full_nutrition => archived.macros,
+                _ => self.get_food_macros(&entry.food_id),
+            },
+        };
+        macros.map(|macros| macros.scaled(entry.servings))
+    }
+
+    pub fn current_food_version(&self, food_id: &str) -> Option<u32> {
+        self.basic_foods.get(food_id).map(|food| food.version)
+            .or_else(|| self.composite_foods.get(food_id).map(|food| food.version))
+    }
+
+    /// The archived nutrition of a superseded version, or None when the version is current,
+    /// unknown or not pinned.
+    fn archived_version(&self, food_id: &str, version: Option<u32>) -> Option<&FoodVersion> {
+        let version = version.filter(|version| self.current_food_version(food_id) != Some(*version))?;
+        self.food_versions.get(food_id)?.iter().find(|v| v.version == version)
+    }
+
+    /// Calories per serving of a specific version of a food, falling back to the current
+    /// nutrition when the version is unknown or not pinned.
+    pub fn food_calories_at(&self, food_id: &str, version: Option<u32>) -> Option<f32> {
+        match self.archived_version(food_id, version) {
+            Some(archived) => Some(archived.calories_per_serving),
+            None => self.get_food_calories(food_id),
+        }
+    }
+
+    pub fn entry_calories(&self, entry: &FoodLogEntry) -> f32 {
+        if let Some(quick_add) = &entry.quick_add {
+            return quick_add.calories * entry.servings;
+        }
+        self.food_calories_at(&entry.food_id, entry.food_version).unwrap_or(0.0) * entry.servings
+    }
+
+    /// Inserts or replaces a basic food. If this changes the nutrition of the food or of any
+    /// composite food containing it, the previous nutrition is archived as a new version so
+    /// existing log entries keep their original calories, macros and fluid.
+    pub fn save_basic_food(&mut self, mut food: BasicFood) {
+        self.begin_action(self.save_label(&food.id, &food.display_name()));
+        let before = self.nutrition_snapshot(&food.id);
+        food.version = self.current_food_version(&food.id).unwrap_or(food.version);
+        if self.composite_foods.contains_key(&food.id) {
+            self.set_composite_food(&food.id, None);
+        }
+        self.set_basic_food(&food.id.clone(), Some(food));
+        self.archive_changed_versions(before);
+    }
+
+    /// Composite counterpart of [`Database::save_basic_food`].
+    pub fn save_composite_food(&mut self, mut food: CompositeFood) {
+        self.begin_action(self.save_label(&food.id, &food.name));
+        let before = self.nutrition_snapshot(&food.id);
+        food.version = self.current_food_version(&food.id).unwrap_or(food.version);
+        if self.basic_foods.contains_key(&food.id) {
+            self.set_basic_food(&food.id, None);
+        }
+        self.set_composite_food(&food.id.clone(), Some(food));
+        self.archive_changed_versions(before);
+    }
+
+    fn save_label(&self, food_id: &str, name: &str) -> String {
+        if self.food_exists(food_id) {
+            format!("Edit '{}'", name)
+        } else {
+            format!("Add '{}'", name)
+        }
+    }
+
+    /// Pins every log entry of the food, and of composite foods containing it, to the current
+    /// version. Used when a change was a genuine data correction. Returns the number of
+    /// entries updated.
+    pub fn reapply_food_to_history(&mut self, food_id: &str) -> usize {
+        self.begin_action(format!("Re-apply '{}' to history", self.food_display_name(food_id)));
+        let affected = self.food_and_dependents(food_id);
+        let versions: HashMap<String, Option<u32>> = affected.iter()
+            .map(|id| (id.clone(), self.current_food_version(id)))
+            .collect();
+
+        let outdated: Vec<FoodLogEntry> = self.food_logs.values()
+            .flatten()
+            .filter(|entry| versions.get(&entry.food_id).is_some_and(|version| entry.food_version != *version))
+            .cloned()
+            .collect();
+        let updated = outdated.len();
+        for mut entry in outdated {
+            entry.food_version = versions[&entry.food_id];
+            self.set_log_entry(&entry.user_id.clone(), &entry.id.clone(), Some(entry));
+        }
+        updated
+    }
+
+    /// Gives log entries written before entries had ids one derived from their user, date and
+    /// place in the log, so they keep the same id until the database is next saved.
+    pub fn assign_missing_entry_ids(&mut self) {
+        for (user_id, entries) in self.food_logs.iter_mut() {
+            for (index, entry) in entries.iter_mut().enumerate() {
+                if entry.id.is_empty() {
+                    entry.id = format!("{}-{}-{}", user_id, entry.date, index);
+                }
+            }
+        }
+    }
+
+    /// Pins log entries written before versioning existed to the version currently in force.
+    pub fn pin_unversioned_entries(&mut self) {
+        let versions: HashMap<String, u32> = self.basic_foods.values().map(|f| (f.id.clone(), f.version))
+            .chain(self.composite_foods.values().map(|f| (f.id.clone(), f.version)))
+            .collect();
+        for entry in self.food_logs.values_mut().flatten() {
+            if entry.food_version.is_none() {
+                entry.food_version = versions.get(&entry.food_id).copied();
+            }
+        }
+    }
+
+    /// The food itself followed by every composite food that contains it at any depth.
+    fn food_and_dependents(&self, food_id: &str) -> Vec<String> {
+        let mut ids = vec![food_id.to_string()];
+        let mut i = 0;
+        while i < ids.len() {
+            for parent in self.food_usage(&ids[i]).composite_foods {
+                if !ids.contains(&parent) {
+                    ids.push(parent);
+                }
+            }
+            i += 1;
+        }
+        ids
+    }
+
+    fn nutrition(&self, food_id: &str) -> Option<Nutrition> {
+        Some(Nutrition {
+            calories: self.get_food_calories(food_id)?,
+            macros: self.get_food_macros(food_id),
+            fluid_ml: self.get_food_fluid(food_id),
+        })
+    }
+
+    fn nutrition_snapshot(&self, food_id: &str) -> Vec<(String, Nutrition)> {
+        self.food_and_dependents(food_id)
+            .into_iter()
+            .filter_map(|id| self.nutrition(&id).map(|nutrition| (id, nutrition)))
+            .collect()
+    }
+
+    fn archive_changed_versions(&mut self, before: Vec<(String, Nutrition)>) {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        for (id, nutrition) in before {
+            let unchanged = self.nutrition(&id).is_some_and(|current| {
+                (current.calories - nutrition.calories).abs() < 0.001
+                    && current.macros == nutrition.macros
+                    && current.fluid_ml == nutrition.fluid_ml
+            });
+            if unchanged {
+                continue;
+            }
+            let version = if let Some(mut food) = self.basic_foods.get(&id).cloned() {
+                food.version += 1;
+                self.set_basic_food(&id, Some(food.clone()));
+                food.version - 1
+            } else if let Some(mut food) = self.composite_foods.get(&id).cloned() {
+                food.version += 1;
+                self.set_composite_food(&id, Some(food.clone()));
+                food.version - 1
+            } else {
+                continue;
+            };
+            let mut versions = self.food_versions.get(&id).cloned().unwrap_or_default();
+            versions.push(FoodVersion {
+                version,
+                calories_per_serving: nutrition.calories,
+                macros: nutrition.macros,
+                fluid_ml: nutrition.fluid_ml,
+                full_nutrition: true,
+                replaced_on: today.clone(),
+            });
+            self.set_food_versions(&id, Some(versions));
+        }
+    }
+
+    /// Calories per meal on a date, paired with each meal's share of the daily target.
+    /// Returns (meal, consumed, budget) in meal order.
+    pub fn meal_breakdown(&self, user_id: &str, date: &str) -> Vec<(MealType, f32, f32)> {
+        let target = self.calculate_calories(user_id, date).target;
+        let entries = self.log_entries_for(user_id, date);
+        MealType::ALL.iter()
+            .map(|meal| {
+                let consumed = entries.iter()
+                    .filter(|entry| entry.meal == *meal)
+                    .fold(0.0, |total, entry| total + self.entry_calories(entry));
+                (*meal, consumed, target * meal.budget_share())
+            })
+            .collect()
+    }
+
+    /// Entries of a user on a date, in the order they were logged.
+    pub fn log_entries_for(&self, user_id: &str, date: &str) -> Vec<&FoodLogEntry> {
+        self.food_logs.get(user_id)
+            .map_or(Vec::new(), |entries| entries.iter().filter(|entry| entry.date == date).collect())
+    }
+
+    pub fn find_log_entry(&self, user_id: &str, entry_id: &str) -> Option<&FoodLogEntry> {
+        self.food_logs.get(user_id)?.iter().find(|entry| entry.id == entry_id)
+    }
+
+    /// Appends an entry to its user's log and returns the entry id.
+    pub fn add_log_entry(&mut self, entry: FoodLogEntry) -> String {
+        self.begin_action(format!("Log '{}'", self.entry_display_name(&entry)));
+        let id = entry.id.clone();
+        self.set_log_entry(&entry.user_id.clone(), &id, Some(entry));
+        id
+    }
+
+    /// Replaces the entry with the same id in its user's log.
+    pub fn update_log_entry(&mut self, entry: FoodLogEntry) -> Result<(), String> {
+        if self.find_log_entry(&entry.user_id, &entry.id).is_none() {
+            return Err(format!("Log entry '{}' does not exist.", entry.id));
+        }
+        self.begin_action(format!("Edit log entry '{}'", self.entry_display_name(&entry)));
+        self.set_log_entry(&entry.user_id.clone(), &entry.id.clone(), Some(entry));
+        Ok(())
+    }
+
+    pub fn delete_log_entry(&mut self, user_id: &str, entry_id: &str) -> Option<FoodLogEntry> {
+        let entry = self.find_log_entry(user_id, entry_id)?.clone();
+        self.begin_action(format!("Delete log entry '{}'", self.entry_display_name(&entry)));
+        self.set_log_entry(user_id, entry_id, None);
+        Some(entry)
+    }
+
+    pub fn calculate_calories(&self, user_id: &str, date: &str) -> DailySummary {
+        let consumed = self.log_entries_for(user_id, date).iter()
+            .fold(0.0, |total, entry| total + self.entry_calories(entry));
+        let burned = self.exercise_entries_for(user_id, date).iter()
+            .fold(0.0, |total, entry| total + entry.calories_burned);
+        let (base_target, eat_back) = self.user_by_id(user_id)
+            .map_or((0.0, false), |user| (user.profile.calculate_target_calories(), user.profile.eat_back_exercise));
+        let target = if eat_back { base_target + burned } else { base_target };
+        DailySummary { consumed, base_target, burned, target, remaining: target - consumed }
+    }
+
+    /// Whether a timed entry falls outside its user's eating window. Entries without a time, and
+    /// users without a fasting schedule, never do.
+    pub fn outside_eating_window(&self, entry: &FoodLogEntry) -> bool {
+        let schedule = self.user_by_id(&entry.user_id).and_then(|user| user.profile.fasting.as_ref());
+        match (schedule, &entry.time) {
+            (Some(schedule), Some(time)) => schedule.in_window(time) == Some(false),
+            _ => false,
+        }
+    }
+
+    /// Whether the user kept to their eating window on a date: None without a schedule or
+    /// without any timed entries that day.
+    pub fn kept_fast(&self, user_id: &str, date: &str) -> Option<bool> {
+        self.user_by_id(user_id)?.profile.fasting.as_ref()?;
+        let timed: Vec<&FoodLogEntry> = self.log_entries_for(user_id, date).into_iter()
+            .filter(|entry| entry.time.is_some())
+            .collect();
+        (!timed.is_empty()).then(|| timed.iter().all(|entry| !self.outside_eating_window(entry)))
+    }
+
+    /// Water of a user on a date, in the order it was logged.
+    pub fn water_entries_for(&self, user_id: &str, date: &str) -> Vec<&WaterEntry> {
+        self.water_logs.get(user_id)
+            .map_or(Vec::new(), |entries| entries.iter().filter(|entry| entry.date == date).collect())
+    }
+
+    pub fn find_water_entry(&self, user_id: &str, entry_id: &str) -> Option<&WaterEntry> {
+        self.water_logs.get(user_id)?.iter().find(|entry| entry.id == entry_id)
+    }
+
+    pub fn add_water_entry(&mut self, entry: WaterEntry) -> String {
+        self.begin_action(format!("Drink {:.0} ml of water", entry.amount_ml));
+        let id = entry.id.clone();
+        self.set_water_entry(&entry.user_id.clone(), &id, Some(entry));
+        id
+    }
+
+    pub fn delete_water_entry(&mut self, user_id: &str, entry_id: &str) -> Option<WaterEntry> {
+        let entry = self.find_water_entry(user_id, entry_id)?.clone();
+        self.begin_action(format!("Delete {:.0} ml of water", entry.amount_ml));
+        self.set_water_entry(user_id, entry_id, None);
+        Some(entry)
+    }
+
+    /// Fluid drunk on a date: water plus beverage foods, in ml.
+    pub fn fluid_for(&self, user_id: &str, date: &str) -> f32 {
+        let water = self.water_entries_for(user_id, date).iter()
+            .fold(0.0, |total, entry| total + entry.amount_ml);
+        self.log_entries_for(user_id, date).iter()
+            .fold(water, |total, entry| total + self.entry_fluid(entry))
+    }
+
+    /// Exercise of a user on a date, in the order it was logged.
+    pub fn exercise_entries_for(&self, user_id: &str, date: &str) -> Vec<&ExerciseEntry> {
+        self.exercise_logs.get(user_id)
+            .map_or(Vec::new(), |entries| entries.iter().filter(|entry| entry.date == date).collect())
+    }
+
+    pub fn find_exercise_entry(&self, user_id: &str, entry_id: &str) -> Option<&ExerciseEntry> {
+        self.exercise_logs.get(user_id)?.iter().find(|entry| entry.id == entry_id)
+    }
+
+    /// Adds or replaces an exercise entry.
+    pub fn save_exercise_entry(&mut self, entry: ExerciseEntry) {
+        let verb = if self.find_exercise_entry(&entry.user_id, &entry.id).is_some() { "Edit" } else { "Log" };
+        self.begin_action(format!("{} {}", verb, entry.activity.label().to_lowercase()));
+        self.set_exercise_entry(&entry.user_id.clone(), &entry.id.clone(), Some(entry));
+    }
+
+    pub fn delete_exercise_entry(&mut self, user_id: &str, entry_id: &str) -> Option<ExerciseEntry> {
+        let entry = self.find_exercise_entry(user_id, entry_id)?.clone();
+        self.begin_action(format!("Delete {}", entry.activity.label().to_lowercase()));
+        self.set_exercise_entry(user_id, entry_id, None);
+        Some(entry)
+    }
+
+    pub fn food_exists(&self, food_id: &str) -> bool {
+        self.basic_foods.contains_key(food_id) || self.composite_foods.contains_key(food_id)
+    }
+
+    /// Returns true if `food_id` is `needle` or is a composite food containing it at any depth.
+    pub fn contains_food(&self, food_id: &str, needle: &str) -> bool {
+        if food_id == needle {
+            return true;
+        }
+        self.composite_foods.get(food_id).is_some_and(|food| {
+            food.components.iter().any(|c| self.contains_food(&c.food_id, needle))
+        })
+    }
+
+    pub fn food_usage(&self, food_id: &str) -> FoodUsage {
+        let mut composite_foods: Vec<String> = self.composite_foods
+            .values()
+            .filter(|food| food.components.iter().any(|c| c.food_id == food_id))
+            .map(|food| food.id.clone())
+            .collect();
+        composite_foods.sort();
+
+        let log_entries = self.food_logs
+            .values()
+            .flatten()
+            .filter(|entry| entry.food_id == food_id)
+            .count();
+
+        FoodUsage { composite_foods, log_entries }
+    }
+
+    /// What a cascading delete of the food by `user_id` would remove.
+    pub fn delete_impact(&self, food_id: &str, user_id: &str) -> DeleteImpact {
+        let to_delete = self.food_and_dependents(food_id);
+        let composite_foods: Vec<String> = to_delete[1..].to_vec();
+        let entries = self.food_logs.values()
+            .flatten()
+            .filter(|entry| to_delete.contains(&entry.food_id));
+        let others = composite_foods.iter().filter(|id| self.food_owner(id) != Some(user_id)).count()
+            + entries.clone().filter(|entry| entry.user_id != user_id).count();
+        DeleteImpact { composite_foods, log_entries: entries.count(), others }
+    }
+
+    /// Deletes a food. If the food is still referenced, the deletion is refused unless
+    /// `cascade` is set, in which case every composite food that (transitively) contains it
+    /// and every log entry pointing at any of the removed foods are deleted too. A cascade
+    /// that would reach foods or entries of users other than `user_id` is refused.
+    pub fn delete_food(&mut self, food_id: &str, user_id: &str, cascade: bool) -> Result<(), String> {
+        if !self.food_exists(food_id) {
+            return Err(format!("Food '{}' does not exist.", food_id));
+        }
+        let others = if cascade { self.delete_impact(food_id, user_id).others } else { 0 };
+        if others > 0 {
+            return Err(format!(
+                "'{}' is used by {} food(s) or log entry(ies) of other users, so it cannot be deleted.",
+                self.food_display_name(food_id),
+                others
+            ));
+        }
+
+        let usage = self.food_usage(food_id);
+        if !usage.is_unused() && !cascade {
+            return Err(format!(
+                "Food '{}' is used by {} composite food(s) and {} log entry(ies).",
+                self.food_display_name(food_id),
+                usage.composite_foods.len(),
+                usage.log_entries
+            ));
+        }
+
+        self.begin_action(format!("Delete '{}'", self.food_display_name(food_id)));
+        let to_delete = self.food_and_dependents(food_id);
+        let entries: Vec<(String, String)> = self.food_logs.values()
+            .flatten()
+            .filter(|entry| to_delete.contains(&entry.food_id))
+            .map(|entry| (entry.user_id.clone(), entry.id.clone()))
+            .collect();
+        for (user_id, entry_id) in entries {
+            self.set_log_entry(&user_id, &entry_id, None);
+        }
+        for id in &to_delete {
+            if self.basic_foods.contains_key(id) {
+                self.set_basic_food(id, None);
+            }
+            if self.composite_foods.contains_key(id) {
+                self.set_composite_food(id, None);
+            }
+            if self.food_versions.contains_key(id) {
+                self.set_food_versions(id, None);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How hunger before eating and mood afterwards relate to the calories of annotated entries.
+#[derive(Debug, Clone, Default)]
+pub struct AnnotationReport {
+    pub by_hunger: Vec<(u8, f32, usize)>, // (level, average kcal per entry, entries)
+    pub by_mood: Vec<(u8, f32, usize)>,
+    pub hunger_correlation: Option<f32>, // Pearson r between hunger and entry calories
+    pub mood_correlation: Option<f32>,
+}
+
+impl Database {
+    pub fn annotation_report(&self, user_id: &str) -> AnnotationReport {
+        let entries: Vec<&FoodLogEntry> = self.food_logs.get(user_id)
+            .map_or(Vec::new(), |entries| entries.iter().collect());
+
+        let hunger: Vec<(f32, f32)> = entries.iter()
+            .filter_map(|entry| entry.hunger.map(|level| (level as f32, self.entry_calories(entry))))
+            .collect();
+        let mood: Vec<(f32, f32)> = entries.iter()
+            .filter_map(|entry| entry.mood.map(|level| (level as f32, self.entry_calories(entry))))
+            .collect();
+
+        AnnotationReport {
+            by_hunger: average_by_level(&hunger),
+            by_mood: average_by_level(&mood),
+            hunger_correlation: pearson(&hunger),
+            mood_correlation: pearson(&mood),
+        }
+    }
+}
+
+fn average_by_level(samples: &[(f32, f32)]) -> Vec<(u8, f32, usize)> {
+    (1..=5u8)
+        .filter_map(|level| {
+            let calories: Vec<f32> = samples.iter()
+                .filter(|(l, _)| *l as u8 == level)
+                .map(|(_, c)| *c)
+                .collect();
+            if calories.is_empty() {
+                None
+            } else {
+                Some((level, calories.iter().sum::<f32>() / calories.len() as f32, calories.len()))
+            }
+        })
+        .collect()
+}
+
+/// Pearson correlation coefficient, or None when there are too few or constant samples.
+fn pearson(samples: &[(f32, f32)]) -> Option<f32> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let n = samples.len() as f32;
+    let mean_x = samples.iter().map(|(x, _)| x).sum::<f32>() / n;
+    let mean_y = samples.iter().map(|(_, y)| y).sum::<f32>() / n;
+    let cov: f32 = samples.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let var_x: f32 = samples.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let var_y: f32 = samples.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+    if var_x == 0.0 || var_y == 0.0 {
+        None
+    } else {
+        Some(cov / (var_x.sqrt() * var_y.sqrt()))
+    }
+}
+
+/// Everything in the database that refers to a given food.
+#[derive(Debug, Clone, Default)]
+pub struct FoodUsage {
+    pub composite_foods: Vec<String>,
+    pub log_entries: usize,
+}
+
+/// What a cascading food delete would remove besides the food itself.
+#[derive(Debug, Clone, Default)]
+pub struct DeleteImpact {
+    pub composite_foods: Vec<String>, // Containing the food at any depth
+    pub log_entries: usize,           // Of the food and of those composite foods
+    pub others: usize,                // How many of those belong to other users
+}
+
+impl DeleteImpact {
+    pub fn is_empty(&self) -> bool {
+        self.composite_foods.is_empty() && self.log_entries == 0
+    }
+}
+
+impl FoodUsage {
+    pub fn is_unused(&self) -> bool {
+        self.composite_foods.is_empty() && self.log_entries == 0
+    }
+}
@@ -0,0 +1,183 @@
+//! Nutrition over a range of dates, built from the per-day totals of [`Database`].
+
+use chrono::{Datelike, Duration, NaiveDate};
+use crate::models::{Database, Macros, WeightEntry};
+
+/// Calories of one day against that day's target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DayTotal {
+    pub date: NaiveDate,
+    pub consumed: f32,
+    pub target: f32,
+    pub logged: bool, // False for days without any entries
+    pub fluid_ml: f32, // Water and beverages
+    pub water_goal_ml: f32,
+    pub kept_fast: Option<bool>, // None without a fasting schedule or timed entries
+}
+
+impl DayTotal {
+    /// How far the day was from its target, in kcal.
+    pub fn deviation(&self) -> f32 {
+        (self.consumed - self.target).abs()
+    }
+}
+
+/// Preset ranges offered by the front ends, ending on a given date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportPeriod {
+    Week,  // The 7 days up to the end date
+    Month, // The calendar month of the end date, up to the end date
+}
+
+impl ReportPeriod {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportPeriod::Week => "Week",
+            ReportPeriod::Month => "Month",
+        }
+    }
+
+    /// First date of the period ending on `end`.
+    pub fn start(&self, end: NaiveDate) -> NaiveDate {
+        match self {
+            ReportPeriod::Week => end - Duration::days(6),
+            ReportPeriod::Month => end.with_day(1).unwrap_or(end),
+        }
+    }
+}
+
+/// Totals for a date range. Averages, over/under counts and best/worst days only consider
+/// days with entries, so a day that was not tracked does not count as a day under target.
+#[derive(Debug, Clone)]
+pub struct RangeReport {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub days: Vec<DayTotal>, // Every date in the range, in order
+    pub total_consumed: f32,
+    pub total_target: f32,
+    pub logged_days: usize,
+    pub days_over: usize,
+    pub days_under: usize,
+    pub best_day: Option<DayTotal>,  // Closest to the target
+    pub worst_day: Option<DayTotal>, // Furthest from the target
+    pub macros: Macros, // Grams over the range, from the entries with known macros
+    pub hydrated_days: usize, // Days with any fluid logged
+    pub total_fluid_ml: f32,
+    pub days_water_goal_met: usize,
+    pub fasting_days: usize, // Days the eating window could be checked
+    pub fasting_days_kept: usize,
+    pub longest_fasting_streak: usize,
+}
+
+impl RangeReport {
+    pub fn daily_average(&self) -> f32 {
+        if self.logged_days == 0 { 0.0 } else { self.total_consumed / self.logged_days as f32 }
+    }
+
+    pub fn average_target(&self) -> f32 {
+        if self.logged_days == 0 { 0.0 } else { self.total_target / self.logged_days as f32 }
+    }
+
+    /// Average fluid on the days any was logged.
+    pub fn average_fluid_ml(&self) -> f32 {
+        if self.hydrated_days == 0 { 0.0 } else { self.total_fluid_ml / self.hydrated_days as f32 }
+    }
+}
+
+/// Calories of each day from `start` to `end` inclusive.
+pub fn daily_totals(db: &Database, user_id: &str, start: NaiveDate, end: NaiveDate) -> Vec<DayTotal> {
+    let water_goal_ml = db.user_by_id(user_id).map_or(0.0, |user| user.profile.water_goal_ml());
+    start.iter_days()
+        .take_while(|date| *date <= end)
+        .map(|date| {
+            let key = date.format("%Y-%m-%d").to_string();
+            let summary = db.calculate_calories(user_id, &key);
+            DayTotal {
+                date,
+                consumed: summary.consumed,
+                target: summary.target,
+                logged: !db.log_entries_for(user_id, &key).is_empty(),
+                fluid_ml: db.fluid_for(user_id, &key),
+                water_goal_ml,
+                kept_fast: db.kept_fast(user_id, &key),
+            }
+        })
+        .collect()
+}
+
+/// Grams of protein, carbs and fat eaten on a date, from the entries with known macros.
+pub fn day_macros(db: &Database, user_id: &str, date: NaiveDate) -> Macros {
+    let mut macros = Macros::default();
+    for entry in db.log_entries_for(user_id, &date.format("%Y-%m-%d").to_string()) {
+        if let Some(entry_macros) = db.entry_macros(entry) {
+            macros.add(&entry_macros);
+        }
+    }
+    macros
+}
+
+pub fn range_report(db: &Database, user_id: &str, start: NaiveDate, end: NaiveDate) -> RangeReport {
+    let days = daily_totals(db, user_id, start, end);
+    let logged: Vec<&DayTotal> = days.iter().filter(|day| day.logged).collect();
+
+    let mut macros = Macros::default();
+    for day in &logged {
+        macros.add(&day_macros(db, user_id, day.date));
+    }
+
+    let by_deviation = |a: &&&DayTotal, b: &&&DayTotal| a.deviation().total_cmp(&b.deviation());
+    RangeReport {
+        start,
+        end,
+        total_consumed: logged.iter().fold(0.0, |total, day| total + day.consumed),
+        total_target: logged.iter().fold(0.0, |total, day| total + day.target),
+        logged_days: logged.len(),
+        days_over: logged.iter().filter(|day| day.consumed > day.target).count(),
+        days_under: logged.iter().filter(|day| day.consumed <= day.target).count(),
+        best_day: logged.iter().min_by(by_deviation).map(|day| **day),
+        worst_day: logged.iter().max_by(by_deviation).map(|day| **day),
+        macros,
+        hydrated_days: days.iter().filter(|day| day.fluid_ml > 0.0).count(),
+        total_fluid_ml: days.iter().fold(0.0, |total, day| total + day.fluid_ml),
+        days_water_goal_met: days.iter().filter(|day| day.fluid_ml > 0.0 && day.fluid_ml >= day.water_goal_ml).count(),
+        fasting_days: days.iter().filter(|day| day.kept_fast.is_some()).count(),
+        fasting_days_kept: days.iter().filter(|day| day.kept_fast == Some(true)).count(),
+        longest_fasting_streak: days.split(|day| day.kept_fast != Some(true)).map(|run| run.len()).max().unwrap_or(0),
+        days,
+    }
+}
+
+/// Consecutive days up to `today` on which the user kept to their eating window. Today only
+/// breaks the streak once it has an entry outside the window, since the day is not over.
+pub fn current_fasting_streak(db: &Database, user_id: &str, today: NaiveDate) -> usize {
+    let kept = |date: NaiveDate| db.kept_fast(user_id, &date.format("%Y-%m-%d").to_string());
+    let kept_today = kept(today);
+    if kept_today == Some(false) {
+        return 0;
+    }
+    let mut streak = usize::from(kept_today == Some(true));
+    let mut date = today - Duration::days(1);
+    while kept(date) == Some(true) {
+        streak += 1;
+        date -= Duration::days(1);
+    }
+    streak
+}
+
+/// Trailing moving average of the weights, one point per entry: the mean of the entries in
+/// the `window_days` days up to and including that entry's date.
+pub fn weight_moving_average(entries: &[WeightEntry], window_days: i64) -> Vec<(NaiveDate, f32)> {
+    let dated: Vec<(NaiveDate, f32)> = entries.iter()
+        .filter_map(|entry| Some((NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d").ok()?, entry.weight_kg)))
+        .collect();
+    dated.iter()
+        .map(|(date, _)| {
+            let from = *date - Duration::days(window_days - 1);
+            let window: Vec<f32> = dated.iter()
+                .filter(|(d, _)| *d >= from && d <= date)
+                .map(|(_, weight)| *weight)
+                .collect();
+            (*date, window.iter().sum::<f32>() / window.len() as f32)
+        })
+        .collect()
+}
@@ -0,0 +1,356 @@
+//! Ranked food search over names, brands and keywords.
+//!
+//! Text is folded before comparing, so "Crème" matches "creme". Each query term is matched
+//! against the words of a food, scoring an exact word highest, then a prefix of a word, then a
+//! word one typo away (Damerau-Levenshtein), then a plain substring. Foods the user logged
+//! recently rank above equally good matches.
+//!
+//! Searches go through a [`SearchIndex`] the database keeps up to date on every food write, so
+//! their cost depends on how many words and foods match rather than on the catalog size. An
+//! empty query lists only the user's recent foods, for the same reason.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound;
+use std::sync::atomic::{AtomicU64, Ordering};
+use chrono::NaiveDate;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use crate::models::Database;
+
+// How much a match in each field counts, relative to the name
+const NAME_WEIGHT: f32 = 1.0;
+const BRAND_WEIGHT: f32 = 0.9;
+const KEYWORD_WEIGHT: f32 = 0.8;
+
+// Added to the score of a food logged today, fading as the last use gets older
+const RECENCY_BONUS: f32 = 0.2;
+const RECENCY_HALF_LIFE_DAYS: f32 = 7.0;
+
+// Terms shorter than this must match exactly or as a prefix; a single edit matches too much
+const MIN_TYPO_LEN: usize = 4;
+// Longest partly typed word checked for a typo, as in "chik" for "chicken"
+const MAX_TYPO_PREFIX_LEN: usize = 7;
+
+// How many recently logged foods an empty query lists
+const RECENT_FOODS: usize = 20;
+
+// Stamps index changes. Shared by all indexes, so a rebuilt index never repeats an earlier stamp.
+static VERSIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Lowercases text and strips accents.
+pub fn fold(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).flat_map(char::to_lowercase).collect()
+}
+
+/// The folded words of a text, split on anything that is not a letter or digit.
+pub fn terms(text: &str) -> Vec<String> {
+    fold(text).split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// How well a folded query term matches a folded word, from 0 (no match) to 1 (the same word).
+pub fn term_match(term: &str, word: &str) -> f32 {
+    if term == word {
+        return 1.0;
+    }
+    if word.starts_with(term) {
+        return 0.8;
+    }
+    let term: Vec<char> = term.chars().collect();
+    let word: Vec<char> = word.chars().collect();
+    if term.len() >= MIN_TYPO_LEN {
+        if typo_distance(&term, &word, 1).is_some() {
+            return 0.6;
+        }
+        // A typo in the part typed so far
+        if term.len() <= MAX_TYPO_PREFIX_LEN
+            && word.len() > term.len()
+            && typo_distance(&term, &word[..term.len()], 1).is_some()
+        {
+            return 0.4;
+        }
+    }
+    if term.len() >= 3 && word.windows(term.len()).any(|window| window == term.as_slice()) {
+        return 0.3;
+    }
+    0.0
+}
+
+/// Damerau-Levenshtein distance (optimal string alignment), or None when it exceeds `max`.
+fn typo_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    // Three rows of the edit matrix: two back, previous and current
+    let mut before: Vec<usize> = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        if current.iter().min().is_some_and(|&lowest| lowest > max) {
+            return None;
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Some(previous[b.len()]).filter(|&distance| distance <= max)
+}
+
+/// The searchable words of a food with the weight of the field each came from.
+fn food_words(db: &Database, food_id: &str) -> Vec<(String, f32)> {
+    let (name, brand, keywords) = match (db.basic_foods.get(food_id), db.composite_foods.get(food_id)) {
+        (Some(food), _) => (&food.name, food.brand.as_deref(), &food.keywords),
+        (_, Some(food)) => (&food.name, None, &food.keywords),
+        _ => return Vec::new(),
+    };
+    let weighted = |text: &str, weight: f32| terms(text).into_iter().map(move |word| (word, weight));
+    weighted(name, NAME_WEIGHT)
+        .chain(brand.into_iter().flat_map(|brand| weighted(brand, BRAND_WEIGHT)))
+        .chain(keywords.iter().flat_map(|keyword| weighted(keyword, KEYWORD_WEIGHT)))
+        .collect()
+}
+
+/// Inverted index from folded words to the foods that contain them, with lookups for the
+/// words a query term can match: a sorted word list for prefixes, one-letter deletions for
+/// typos and trigrams for substrings.
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    postings: BTreeMap<String, HashSet<String>>, // Word -> ids of the foods containing it
+    food_words: HashMap<String, Vec<(String, f32)>>, // Food id -> its words with field weights
+    words: Vec<String>, // Indexed words by id; the ids of words no food uses any more are reused
+    free_ids: Vec<u32>,
+    word_ids: HashMap<String, u32>,
+    variants: HashMap<String, HashSet<u32>>, // A word, or a prefix of it, less at most one letter
+    trigrams: HashMap<String, HashSet<u32>>, // Words containing each three letters
+    version: u64,
+}
+
+impl SearchIndex {
+    /// Indexes a food under its words, replacing what was indexed for it before.
+    fn insert(&mut self, food_id: &str, words: Vec<(String, f32)>) {
+        self.remove(food_id);
+        for (word, _) in &words {
+            self.add_word(word);
+            self.postings.entry(word.clone()).or_default().insert(food_id.to_string());
+        }
+        self.food_words.insert(food_id.to_string(), words);
+        self.version = VERSIONS.fetch_add(1, Ordering::Relaxed) + 1;
+    }
+
+    fn remove(&mut self, food_id: &str) {
+        for (word, _) in self.food_words.remove(food_id).unwrap_or_default() {
+            if let Some(foods) = self.postings.get_mut(&word) {
+                foods.remove(food_id);
+                if foods.is_empty() {
+                    self.postings.remove(&word);
+                    self.remove_word(&word);
+                }
+            }
+        }
+        self.version = VERSIONS.fetch_add(1, Ordering::Relaxed) + 1;
+    }
+
+    /// Adds a word to the typo and substring lookups the first time it is seen.
+    fn add_word(&mut self, word: &str) {
+        if self.word_ids.contains_key(word) {
+            return;
+        }
+        let id = match self.free_ids.pop() {
+            Some(id) => {
+                self.words[id as usize] = word.to_string();
+                id
+            }
+            None => {
+                self.words.push(word.to_string());
+                self.words.len() as u32 - 1
+            }
+        };
+        self.word_ids.insert(word.to_string(), id);
+
+        let (variants, trigrams) = lookup_keys(word);
+        for key in variants {
+            self.variants.entry(key).or_default().insert(id);
+        }
+        for trigram in trigrams {
+            self.trigrams.entry(trigram).or_default().insert(id);
+        }
+    }
+
+    /// Drops a word no food uses any more from the lookups and frees its id.
+    fn remove_word(&mut self, word: &str) {
+        let Some(id) = self.word_ids.remove(word) else {
+            return;
+        };
+        let (variants, trigrams) = lookup_keys(word);
+        for (table, keys) in [(&mut self.variants, variants), (&mut self.trigrams, trigrams)] {
+            for key in keys {
+                if let Some(ids) = table.get_mut(&key) {
+                    ids.remove(&id);
+                    if ids.is_empty() {
+                        table.remove(&key);
+                    }
+                }
+            }
+        }
+        self.words[id as usize].clear();
+        self.free_ids.push(id);
+    }
+
+    /// The indexed words a folded query term matches, with how well each matches.
+    fn matching_words(&self, term: &str) -> HashMap<&str, f32> {
+        let mut candidates: HashSet<&str> = self.postings.range::<str, _>((Bound::Included(term), Bound::Unbounded))
+            .take_while(|(word, _)| word.starts_with(term))
+            .map(|(word, _)| word.as_str())
+            .collect();
+        let chars: Vec<char> = term.chars().collect();
+        if chars.len() >= MIN_TYPO_LEN {
+            for key in deletions(&chars) {
+                let ids = self.variants.get(&key).into_iter().flatten();
+                candidates.extend(ids.map(|id| self.words[*id as usize].as_str()));
+            }
+        }
+        if chars.len() >= 3 {
+            // Words containing the term contain all of its trigrams; start from the rarest
+            let mut lists: Vec<&HashSet<u32>> = Vec::new();
+            for window in chars.windows(3) {
+                match self.trigrams.get(&window.iter().collect::<String>()) {
+                    Some(ids) => lists.push(ids),
+                    None => lists.clear(),
+                }
+                if lists.is_empty() {
+                    break;
+                }
+            }
+            if let Some(rarest) = lists.iter().min_by_key(|ids| ids.len()) {
+                candidates.extend(rarest.iter().map(|id| self.words[*id as usize].as_str()));
+            }
+        }
+        candidates.into_iter()
+            .filter(|word| self.postings.contains_key(*word))
+            .map(|word| (word, term_match(term, word)))
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect()
+    }
+
+    /// Foods matching the query terms with their match scores. With `match_all` every term has
+    /// to match a word of the food; otherwise any one is enough.
+    fn matches(&self, query: &[String], match_all: bool) -> HashMap<&str, f32> {
+        let mut term_scores: HashMap<&str, Vec<f32>> = HashMap::new();
+        for (index, term) in query.iter().enumerate() {
+            for (word, quality) in self.matching_words(term) {
+                for food_id in &self.postings[word] {
+                    let weight = self.food_words[food_id].iter()
+                        .filter(|(w, _)| w == word)
+                        .map(|(_, weight)| *weight)
+                        .fold(0.0, f32::max);
+                    let scores = term_scores.entry(food_id.as_str()).or_insert_with(|| vec![0.0; query.len()]);
+                    scores[index] = scores[index].max(quality * weight);
+                }
+            }
+        }
+        term_scores.into_iter()
+            .filter(|(_, scores)| !match_all || scores.iter().all(|score| *score > 0.0))
+            .map(|(food_id, scores)| (food_id, scores.iter().sum::<f32>() / scores.len() as f32))
+            .collect()
+    }
+}
+
+/// The keys a word is listed under: its typo variants and its trigrams.
+fn lookup_keys(word: &str) -> (HashSet<String>, HashSet<String>) {
+    let chars: Vec<char> = word.chars().collect();
+    let mut variants: HashSet<String> = HashSet::new();
+    if chars.len() >= MIN_TYPO_LEN - 1 {
+        variants.extend(deletions(&chars));
+    }
+    for len in MIN_TYPO_LEN..=MAX_TYPO_PREFIX_LEN.min(chars.len().saturating_sub(1)) {
+        variants.extend(deletions(&chars[..len]));
+    }
+    let trigrams = chars.windows(3).map(|window| window.iter().collect()).collect();
+    (variants, trigrams)
+}
+
+/// A word and every way of deleting one of its letters.
+fn deletions(chars: &[char]) -> HashSet<String> {
+    let mut keys: HashSet<String> = (0..chars.len())
+        .map(|skip| chars.iter().enumerate().filter(|(i, _)| *i != skip).map(|(_, c)| c).collect())
+        .collect();
+    keys.insert(chars.iter().collect());
+    keys
+}
+
+impl Database {
+    /// Builds the search index from scratch, for a database just loaded from disk.
+    pub fn rebuild_search_index(&mut self) {
+        self.search_index = SearchIndex::default();
+        let ids: Vec<String> = self.basic_foods.keys().chain(self.composite_foods.keys()).cloned().collect();
+        for id in ids {
+            self.reindex_food(&id);
+        }
+    }
+
+    /// Brings the index up to date with the food stored under `food_id`, or with its removal.
+    pub(crate) fn reindex_food(&mut self, food_id: &str) {
+        if self.food_exists(food_id) {
+            let words = food_words(self, food_id);
+            self.search_index.insert(food_id, words);
+        } else {
+            self.search_index.remove(food_id);
+        }
+    }
+
+    /// Changes whenever a food is added, edited or removed, or the database is reloaded, so
+    /// front ends can keep search results until then.
+    pub fn food_catalog_version(&self) -> u64 {
+        self.search_index.version
+    }
+}
+
+/// Last date each food was logged by the user.
+fn last_used<'a>(db: &'a Database, user_id: &str) -> HashMap<&'a str, NaiveDate> {
+    let mut last_used: HashMap<&str, NaiveDate> = HashMap::new();
+    for entry in db.food_logs.get(user_id).into_iter().flatten() {
+        if let Ok(date) = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d") {
+            let used = last_used.entry(entry.food_id.as_str()).or_insert(date);
+            *used = (*used).max(date);
+        }
+    }
+    last_used
+}
+
+/// Ids of the foods the user can see that match the query, best match first. An empty query
+/// gives a short list of the foods the user logged most recently instead.
+pub fn search_foods(db: &Database, user_id: &str, query: &str, match_all: bool) -> Vec<String> {
+    let query = terms(query);
+    let last_used = last_used(db, user_id);
+    if query.is_empty() {
+        let mut recent: Vec<(NaiveDate, String, &str)> = last_used.into_iter()
+            .filter(|(id, _)| db.food_visible_to(id, user_id))
+            .map(|(id, date)| (date, db.food_display_name(id), id))
+            .collect();
+        recent.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| (&a.1, a.2).cmp(&(&b.1, b.2))));
+        return recent.into_iter().take(RECENT_FOODS).map(|(_, _, id)| id.to_string()).collect();
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let recency = |food_id: &str| {
+        last_used.get(food_id).map_or(0.0, |date| {
+            let days = (today - *date).num_days().max(0) as f32;
+            RECENCY_BONUS * 0.5f32.powf(days / RECENCY_HALF_LIFE_DAYS)
+        })
+    };
+    let mut hits: Vec<(f32, String, &str)> = db.search_index.matches(&query, match_all).into_iter()
+        .filter(|(id, _)| db.food_visible_to(id, user_id))
+        .map(|(id, score)| (score + recency(id), db.food_display_name(id), id))
+        .collect();
+    hits.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| (&a.1, a.2).cmp(&(&b.1, b.2))));
+    hits.into_iter().map(|(_, _, id)| id.to_string()).collect()
+}
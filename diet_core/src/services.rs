@@ -0,0 +1,220 @@
+//! Actions the front ends offer, with the validation each of them needs.
+
+use crate::models::{
+    ActivityLevel, CalorieCalculationMethod, CompositeFood, Database, ExerciseEntry, FoodLogEntry, Gender, RecipeYield,
+    RestrictionPolicy, User, UserProfile, WaterEntry, WeightEntry,
+};
+use crate::validation::{
+    validate_body, validate_credentials, validate_date, validate_duration, validate_food_name, validate_measurements,
+    validate_servings, validate_time, validate_water_amount,
+};
+
+/// Today's date in the format log entries use.
+pub fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// Details entered when registering.
+#[derive(Debug, Clone)]
+pub struct NewUser {
+    pub username: String,
+    pub password: String,
+    pub gender: Gender,
+    pub height_cm: f32,
+    pub age: u32,
+    pub weight_kg: f32,
+    pub activity_level: ActivityLevel,
+    pub calorie_method: CalorieCalculationMethod,
+}
+
+/// Creates the account and logs it in. Returns the new user_id.
+pub fn register(db: &mut Database, new_user: NewUser) -> Result<String, String> {
+    validate_credentials(&new_user.username, &new_user.password)?;
+    if db.users.contains_key(&new_user.username) {
+        return Err("Username already exists.".to_string());
+    }
+    validate_body(new_user.height_cm, new_user.age, new_user.weight_kg)?;
+
+    let user_id = uuid::Uuid::new_v4().to_string();
+    let profile = UserProfile {
+        gender: new_user.gender,
+        height_cm: new_user.height_cm,
+        age: new_user.age,
+        calorie_method: new_user.calorie_method,
+        weight_kg: new_user.weight_kg,
+        activity_level: new_user.activity_level,
+        allergies: Vec::new(),
+        dietary_requirements: Vec::new(),
+        restriction_policy: RestrictionPolicy::Warn,
+        weight_from_log: false,
+        eat_back_exercise: false,
+        fasting: None,
+    };
+    let user = User {
+        user_id: user_id.clone(),
+        username: new_user.username.clone(),
+        password: new_user.password,
+        profile,
+    };
+    db.users.insert(new_user.username, user);
+    db.weight_logs.insert(user_id.clone(), vec![WeightEntry::new(&today(), new_user.weight_kg)]);
+    db.touch();
+    db.current_user = user_id.clone();
+    Ok(user_id)
+}
+
+/// Checks the credentials and makes the user current. Returns their user_id.
+pub fn login(db: &mut Database, username: &str, password: &str) -> Result<String, String> {
+    let user_id = authenticate(db, username, password)?;
+    db.current_user = user_id.clone();
+    Ok(user_id)
+}
+
+/// Checks the credentials without changing the current user. Returns their user_id.
+pub fn authenticate(db: &Database, username: &str, password: &str) -> Result<String, String> {
+    let Some(user) = db.users.get(username) else {
+        return Err("User not found.".to_string());
+    };
+    if user.password != password {
+        return Err("Incorrect password.".to_string());
+    }
+    Ok(user.user_id.clone())
+}
+
+/// Adds an entry to its user's log after checking the date, time, servings, that the food
+/// is visible to the user and, under a Block policy, that it fits their restrictions.
+/// Food entries are pinned to the food version currently in force. Returns the entry id.
+pub fn log_entry(db: &mut Database, mut entry: FoodLogEntry) -> Result<String, String> {
+    check_entry(db, &entry)?;
+    validate_date(&entry.date)?;
+    if entry.quick_add.is_none() {
+        let blocked = db.user_by_id(&entry.user_id)
+            .is_some_and(|user| user.profile.restriction_policy == RestrictionPolicy::Block);
+        if blocked && db.restriction_conflicts(&entry.user_id, &entry.food_id).iter().any(|c| c.blocks()) {
+            return Err("This food conflicts with your dietary restrictions.".to_string());
+        }
+        entry.food_version = db.current_food_version(&entry.food_id);
+    }
+    Ok(db.add_log_entry(entry))
+}
+
+/// Replaces an existing entry after the same checks as [`log_entry`], except that the date
+/// and food version are left as they were logged.
+pub fn update_entry(db: &mut Database, entry: FoodLogEntry) -> Result<(), String> {
+    check_entry(db, &entry)?;
+    db.update_log_entry(entry)
+}
+
+fn check_entry(db: &Database, entry: &FoodLogEntry) -> Result<(), String> {
+    if db.user_by_id(&entry.user_id).is_none() {
+        return Err(format!("User '{}' does not exist.", entry.user_id));
+    }
+    validate_servings(entry.servings)?;
+    if let Some(time) = &entry.time {
+        validate_time(time)?;
+    }
+    match &entry.quick_add {
+        Some(quick_add) if quick_add.label.trim().is_empty() => Err("Please enter a label.".to_string()),
+        Some(quick_add) if quick_add.calories <= 0.0 => Err("Calories must be a positive number.".to_string()),
+        Some(_) => Ok(()),
+        None if !db.food_visible_to(&entry.food_id, &entry.user_id) => {
+            Err(format!("Food '{}' does not exist.", entry.food_id))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Records a weight log entry after checking the date and measurements.
+pub fn log_weight(db: &mut Database, user_id: &str, entry: WeightEntry) -> Result<(), String> {
+    if db.user_by_id(user_id).is_none() {
+        return Err(format!("User '{}' does not exist.", user_id));
+    }
+    validate_date(&entry.date)?;
+    validate_measurements(entry.weight_kg, entry.waist_cm, entry.body_fat_percent)?;
+    db.log_weight(user_id, entry);
+    Ok(())
+}
+
+/// Adds or updates an exercise entry after checking the date and duration. The calories burned
+/// are estimated from the user's weight on the entry's date. An edit that keeps the activity,
+/// intensity and duration keeps the estimate made when it was logged.
+pub fn log_exercise(db: &mut Database, mut entry: ExerciseEntry) -> Result<String, String> {
+    if db.user_by_id(&entry.user_id).is_none() {
+        return Err(format!("User '{}' does not exist.", entry.user_id));
+    }
+    validate_date(&entry.date)?;
+    validate_duration(entry.duration_min)?;
+    entry.calories_burned = match db.find_exercise_entry(&entry.user_id, &entry.id) {
+        Some(old) if old.activity == entry.activity
+            && old.intensity == entry.intensity
+            && old.duration_min == entry.duration_min => old.calories_burned,
+        _ => entry.estimate_calories(db.weight_on(&entry.user_id, &entry.date)),
+    };
+    let id = entry.id.clone();
+    db.save_exercise_entry(entry);
+    Ok(id)
+}
+
+pub fn log_water(db: &mut Database, entry: WaterEntry) -> Result<String, String> {
+    if db.user_by_id(&entry.user_id).is_none() {
+        return Err(format!("User '{}' does not exist.", entry.user_id));
+    }
+    validate_date(&entry.date)?;
+    validate_water_amount(entry.amount_ml)?;
+    Ok(db.add_water_entry(entry))
+}
+
+/// Checks a new composite food before it is saved: it needs an identifier, a name, at least
+/// one component and a positive number of portions. Trims the name and drops empty keywords.
+pub fn check_composite_food(mut food: CompositeFood) -> Result<CompositeFood, String> {
+    if food.local_id().trim().is_empty() {
+        return Err("Identifier is required.".to_string());
+    }
+    food.name = validate_food_name(&food.name)?;
+    if food.components.is_empty() {
+        return Err("Add at least one component.".to_string());
+    }
+    for component in &food.components {
+        validate_servings(component.servings)?;
+    }
+    if let RecipeYield::Portions(portions) = food.recipe_yield {
+        if portions <= 0.0 {
+            return Err("Portions must be greater than zero.".to_string());
+        }
+    }
+    food.keywords = food.keywords.iter().map(|k| k.trim().to_string()).filter(|k| !k.is_empty()).collect();
+    Ok(food)
+}
+
+/// Splits a comma-separated keyword list, dropping empty entries.
+pub fn split_keywords(keywords: &str) -> Vec<String> {
+    keywords.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+/// Finds a food the user can see by id, or by name when that name is unambiguous
+/// (case-insensitive, with or without the brand). Returns the food id.
+pub fn resolve_food(db: &Database, user_id: &str, reference: &str) -> Result<String, String> {
+    if db.food_visible_to(reference, user_id) {
+        return Ok(reference.to_string());
+    }
+    let wanted = reference.trim().to_lowercase();
+    let mut matches: Vec<&String> = db.basic_foods.values()
+        .filter(|food| food.name.to_lowercase() == wanted || food.display_name().to_lowercase() == wanted)
+        .map(|food| &food.id)
+        .chain(db.composite_foods.values()
+            .filter(|food| food.name.to_lowercase() == wanted || food.local_id().to_lowercase() == wanted)
+            .map(|food| &food.id))
+        .filter(|id| db.food_visible_to(id, user_id))
+        .collect();
+    matches.sort();
+    matches.dedup();
+    match matches.as_slice() {
+        [id] => Ok(id.to_string()),
+        [] => Err(format!("No food named '{}'.", reference.trim())),
+        ids => Err(format!(
+            "'{}' matches several foods; use one of these ids: {}",
+            reference.trim(),
+            ids.iter().map(|id| id.as_str()).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
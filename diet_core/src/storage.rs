@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::models::Database;
+use crate::undo::UserHistory;
+
+pub const DB_FILE: &str = "database.json";
+
+pub fn load_database() -> Database {
+    load_database_from(DB_FILE)
+}
+
+pub fn save_database(db: &Database) -> std::io::Result<()> {
+    save_database_to(db, DB_FILE)
+}
+
+/// Loads the database at `path`, starting empty if it is missing or unreadable.
+pub fn load_database_from(path: impl AsRef<Path>) -> Database {
+    let mut db = if let Ok(data) = fs::read_to_string(path) {
+        serde_json::from_str(&data).unwrap_or_else(|_| Database::default())
+    } else {
+        Database::default()
+    };
+    db.assign_missing_entry_ids();
+    db.pin_unversioned_entries();
+    db.rebuild_search_index();
+    db.touch();
+    db
+}
+
+pub fn save_database_to(db: &Database, path: impl AsRef<Path>) -> std::io::Result<()> {
+    let data = serde_json::to_string_pretty(db).unwrap();
+    fs::write(path, data)
+}
+
+/// A file kept next to the database and named after it, e.g. "database.session.json".
+fn companion_path(db_path: impl AsRef<Path>, kind: &str) -> PathBuf {
+    db_path.as_ref().with_extension(format!("{}.json", kind))
+}
+
+fn remove_if_exists(path: PathBuf) -> std::io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// The user_id dietctl is logged in as for the database at `db_path`, or empty when logged out.
+/// Kept out of the database so the other front ends cannot change it.
+pub fn load_session(db_path: impl AsRef<Path>) -> String {
+    fs::read_to_string(companion_path(db_path, "session")).unwrap_or_default().trim().to_string()
+}
+
+pub fn save_session(db_path: impl AsRef<Path>, user_id: &str) -> std::io::Result<()> {
+    let path = companion_path(db_path, "session");
+    if user_id.is_empty() {
+        return remove_if_exists(path);
+    }
+    fs::write(path, user_id)
+}
+
+/// When the file at `path` was last written, or None if it does not exist. The front ends
+/// compare it with the time they loaded the file to pick up changes made by the others.
+pub fn modified_time(path: impl AsRef<Path>) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Undo histories saved for the database at `db_path`, keyed by user_id.
+pub fn load_undo_histories(db_path: impl AsRef<Path>) -> HashMap<String, UserHistory> {
+    fs::read_to_string(companion_path(db_path, "undo"))
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Writes the given users' histories into those saved for the database, removing the users
+/// mapped to None, so front ends sharing the database only overwrite what they changed.
+pub fn save_undo_histories(
+    db_path: impl AsRef<Path>,
+    changes: HashMap<String, Option<UserHistory>>,
+) -> std::io::Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let mut histories = load_undo_histories(&db_path);
+    for (user_id, history) in changes {
+        match history {
+            Some(history) => histories.insert(user_id, history),
+            None => histories.remove(&user_id),
+        };
+    }
+    let path = companion_path(db_path, "undo");
+    if histories.is_empty() {
+        return remove_if_exists(path);
+    }
+    let data = serde_json::to_string(&histories).unwrap();
+    fs::write(path, data)
+}
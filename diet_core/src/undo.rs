@@ -0,0 +1,161 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+use crate::commands::Command;
+use crate::models::Database;
+
+// Commands on the same records this close together are undone as one step
+const COALESCE_WINDOW: Duration = Duration::from_millis(1000);
+
+/// Undo and redo stacks of one user.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UserHistory {
+    #[serde(default)]
+    pub persist: bool, // Keep this history after the app exits
+    undo_stack: Vec<Command>,
+    redo_stack: Vec<Command>,
+}
+
+pub struct UndoManager {
+    max_commands: usize, // Per user
+    histories: HashMap<String, UserHistory>, // Key: user_id
+    last_push: Option<Instant>,
+    status: Option<(String, String)>, // User id and outcome of the last undo or redo
+    unsaved: HashSet<String>, // Users whose history changed since it was last saved
+}
+
+impl UndoManager {
+    /// Restores histories saved by a previous run, trimmed to `max_commands` per user.
+    pub fn with_histories(max_commands: usize, mut histories: HashMap<String, UserHistory>) -> Self {
+        for history in histories.values_mut() {
+            history.trim(max_commands);
+        }
+        Self {
+            max_commands,
+            histories,
+            last_push: None,
+            status: None,
+            unsaved: HashSet::new(),
+        }
+    }
+
+    /// Takes the histories saved by another front end, except those changed here since.
+    pub fn reload_histories(&mut self, histories: HashMap<String, UserHistory>) {
+        self.histories.retain(|user_id, _| self.unsaved.contains(user_id));
+        for (user_id, mut history) in histories {
+            if !self.unsaved.contains(&user_id) {
+                history.trim(self.max_commands);
+                self.histories.insert(user_id, history);
+            }
+        }
+    }
+
+    /// The histories changed since the last call, for saving: None for a user who chose not
+    /// to keep theirs, so it is removed from the saved ones.
+    pub fn take_unsaved_histories(&mut self) -> HashMap<String, Option<UserHistory>> {
+        self.unsaved.drain()
+            .map(|user_id| {
+                let history = self.histories.get(&user_id).filter(|history| history.persist).cloned();
+                (user_id, history)
+            })
+            .collect()
+    }
+
+    pub fn is_persisted(&self, user_id: &str) -> bool {
+        self.histories.get(user_id).is_some_and(|history| history.persist)
+    }
+
+    pub fn set_persisted(&mut self, user_id: &str, persist: bool) {
+        self.histories.entry(user_id.to_string()).or_default().persist = persist;
+        self.unsaved.insert(user_id.to_string());
+    }
+
+    /// Records a command the user has just applied. Their redo history is discarded.
+    pub fn push(&mut self, user_id: &str, command: Command) {
+        let recent = self.last_push.is_some_and(|at| at.elapsed() < COALESCE_WINDOW);
+        self.last_push = Some(Instant::now());
+        self.status = None;
+        self.unsaved.insert(user_id.to_string());
+        let history = self.histories.entry(user_id.to_string()).or_default();
+        history.redo_stack.clear();
+        if let Some(top) = history.undo_stack.last_mut() {
+            if recent && top.same_records(&command) {
+                top.merge(command);
+                if top.changes.is_empty() {
+                    history.undo_stack.pop();
+                }
+                return;
+            }
+        }
+        history.undo_stack.push(command);
+        history.trim(self.max_commands);
+    }
+
+    /// Reverts the user's most recent command.
+    pub fn undo(&mut self, user_id: &str, db: &mut Database) {
+        self.step(user_id, db, true);
+    }
+
+    /// Re-applies the user's most recently undone command.
+    pub fn redo(&mut self, user_id: &str, db: &mut Database) {
+        self.step(user_id, db, false);
+    }
+
+    fn step(&mut self, user_id: &str, db: &mut Database, undo: bool) {
+        self.last_push = None;
+        let verb = if undo { "undo" } else { "redo" };
+        let Some(history) = self.histories.get_mut(user_id) else {
+            self.status = Some((user_id.to_string(), format!("Nothing to {}.", verb)));
+            return;
+        };
+        let (from, to) = if undo {
+            (&mut history.undo_stack, &mut history.redo_stack)
+        } else {
+            (&mut history.redo_stack, &mut history.undo_stack)
+        };
+        let Some(command) = from.pop() else {
+            self.status = Some((user_id.to_string(), format!("Nothing to {}.", verb)));
+            return;
+        };
+        self.unsaved.insert(user_id.to_string());
+        if db.can_apply_command(&command, undo) {
+            db.apply_command(&command, undo);
+            let outcome = format!("{}: {}", if undo { "Undone" } else { "Redone" }, command.label);
+            self.status = Some((user_id.to_string(), outcome));
+            to.push(command);
+        } else {
+            // The records were changed since; drop the command rather than overwrite those changes
+            let outcome = format!("Cannot {} '{}': the data has changed since.", verb, command.label);
+            self.status = Some((user_id.to_string(), outcome));
+        }
+    }
+
+    pub fn status(&self, user_id: &str) -> Option<&str> {
+        self.status.as_ref()
+            .filter(|(status_user, _)| status_user == user_id)
+            .map(|(_, outcome)| outcome.as_str())
+    }
+
+    /// Labels of the commands the user can undo, most recent first.
+    pub fn undo_labels(&self, user_id: &str) -> Vec<&str> {
+        self.histories.get(user_id)
+            .map_or(Vec::new(), |history| history.undo_stack.iter().rev().map(|c| c.label.as_str()).collect())
+    }
+
+    /// Labels of the commands the user can redo, next first.
+    pub fn redo_labels(&self, user_id: &str) -> Vec<&str> {
+        self.histories.get(user_id)
+            .map_or(Vec::new(), |history| history.redo_stack.iter().rev().map(|c| c.label.as_str()).collect())
+    }
+}
+
+impl UserHistory {
+    fn trim(&mut self, max_commands: usize) {
+        if self.undo_stack.len() > max_commands {
+            self.undo_stack.drain(..self.undo_stack.len() - max_commands);
+        }
+        if self.redo_stack.len() > max_commands {
+            self.redo_stack.drain(..self.redo_stack.len() - max_commands);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Input checks shared by every front end. Errors are messages ready to show to the user.
+
+use chrono::NaiveDate;
+use crate::models::is_valid_time;
+
+/// Parses an ISO 8601 date ("2023-10-01") that is not in the future.
+pub fn validate_date(date: &str) -> Result<NaiveDate, String> {
+    let parsed = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("'{}' is not a valid date (expected YYYY-MM-DD).", date))?;
+    if parsed > chrono::Local::now().date_naive() {
+        return Err("Cannot add to a future date.".to_string());
+    }
+    Ok(parsed)
+}
+
+/// An optional "HH:MM" time of day. Empty input means no time.
+pub fn validate_time(time: &str) -> Result<Option<String>, String> {
+    let time = time.trim();
+    if time.is_empty() {
+        Ok(None)
+    } else if is_valid_time(time) {
+        Ok(Some(time.to_string()))
+    } else {
+        Err("Time must be in HH:MM format.".to_string())
+    }
+}
+
+pub fn validate_servings(servings: f32) -> Result<(), String> {
+    if servings > 0.0 && servings.is_finite() {
+        Ok(())
+    } else {
+        Err("Servings must be greater than zero.".to_string())
+    }
+}
+
+/// Parses a calorie amount, which may be zero (e.g. water) but not negative.
+pub fn parse_calories(calories: &str) -> Result<f32, String> {
+    match calories.trim().parse::<f32>() {
+        Ok(calories) if calories >= 0.0 && calories.is_finite() => Ok(calories),
+        _ => Err("Calories must be a non-negative number.".to_string()),
+    }
+}
+
+pub fn validate_food_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        Err("Name is required.".to_string())
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+pub fn validate_credentials(username: &str, password: &str) -> Result<(), String> {
+    if username.is_empty() || password.is_empty() {
+        Err("Username and password are required.".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn validate_body(height_cm: f32, age: u32, weight_kg: f32) -> Result<(), String> {
+    if height_cm <= 0.0 || age == 0 || weight_kg <= 0.0 {
+        Err("Invalid height, age, or weight.".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn validate_measurements(weight_kg: f32, waist_cm: Option<f32>, body_fat_percent: Option<f32>) -> Result<(), String> {
+    if !(weight_kg > 0.0 && weight_kg.is_finite()) {
+        Err("Weight must be greater than zero.".to_string())
+    } else if waist_cm.is_some_and(|waist| !(waist > 0.0 && waist.is_finite())) {
+        Err("Waist must be greater than zero.".to_string())
+    } else if body_fat_percent.is_some_and(|fat| !(fat > 0.0 && fat < 100.0)) {
+        Err("Body fat must be between 0 and 100%.".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// An exercise duration in minutes, up to a whole day.
+pub fn validate_duration(duration_min: f32) -> Result<(), String> {
+    if duration_min > 0.0 && duration_min <= 24.0 * 60.0 {
+        Ok(())
+    } else {
+        Err("Duration must be more than zero and at most 24 hours.".to_string())
+    }
+}
+
+/// A drink of water in ml, up to five litres at once.
+pub fn validate_water_amount(amount_ml: f32) -> Result<(), String> {
+    if amount_ml > 0.0 && amount_ml <= 5000.0 {
+        Ok(())
+    } else {
+        Err("Amount must be more than zero and at most 5000 ml.".to_string())
+    }
+}
@@ -1,11 +1,18 @@
+#[derive(Clone, Copy, PartialEq)]
 pub enum AppState {
     Login,
     Register,
     Home,
     AddBasicFood,
     AddCompositeFood,
+    ManageFoods,
     ViewDailyLog,
     AddFoodToLog,
     EditFoodLog,
     UpdateProfile,
+    AnnotationReport,
+    Reports,
+    Charts,
+    BodyLog,
+    ExerciseLog,
 }
\ No newline at end of file
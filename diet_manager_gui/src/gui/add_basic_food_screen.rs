@@ -1,28 +1,67 @@
 use eframe::egui;
-use crate::models::{Database, BasicFood};
+use diet_core::models::{Allergen, Database, BasicFood, FoodTag, Macros, Visibility};
+use diet_core::services::split_keywords;
+use diet_core::validation::{parse_calories, validate_food_name};
 use crate::app_state::AppState;
+use crate::gui::{allergens_editor, beverage_editor, category_and_tags_editor, macros_editor};
 
 pub struct AddBasicFoodScreen {
-    new_food_id: String,
+    new_food_name: String,
+    new_food_brand: String,
+    new_food_description: String,
+    new_food_notes: String,
     new_food_keywords: String,
     new_food_calories: String,
+    new_food_macros: Option<Macros>, // Per serving
+    new_food_fluid_ml: Option<f32>, // Per serving, for beverages
+    new_food_category: Option<String>,
+    new_food_tags: Vec<FoodTag>,
+    new_food_allergens: Vec<Allergen>,
+    share_with_household: bool,
+    duplicate_warning: Option<String>, // Name the user was warned about; saving it again adds it anyway
+    error_message: Option<String>,
 }
 
 impl AddBasicFoodScreen {
     pub fn new() -> Self {
         Self {
-            new_food_id: String::new(),
+            new_food_name: String::new(),
+            new_food_brand: String::new(),
+            new_food_description: String::new(),
+            new_food_notes: String::new(),
             new_food_keywords: String::new(),
             new_food_calories: String::new(),
+            new_food_macros: None,
+            new_food_fluid_ml: None,
+            new_food_category: None,
+            new_food_tags: Vec::new(),
+            new_food_allergens: Vec::new(),
+            share_with_household: true,
+            duplicate_warning: None,
+            error_message: None,
         }
     }
 
     pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState) {
         ui.heading("Add Basic Food");
 
+        if let Some(error) = &self.error_message {
+            ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut self.new_food_name);
+        });
+
         ui.horizontal(|ui| {
-            ui.label("Identifier:");
-            ui.text_edit_singleline(&mut self.new_food_id);
+            ui.label("Brand (optional):");
+            ui.text_edit_singleline(&mut self.new_food_brand);
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Description (optional):");
+            ui.text_edit_singleline(&mut self.new_food_description);
         });
 
         ui.horizontal(|ui| {
@@ -34,24 +73,70 @@ impl AddBasicFoodScreen {
             ui.label("Calories:");
             ui.text_edit_singleline(&mut self.new_food_calories);
         });
+        macros_editor(ui, &mut self.new_food_macros);
+        beverage_editor(ui, &mut self.new_food_fluid_ml);
+
+        category_and_tags_editor(ui, db, "add_basic_food_category", &mut self.new_food_category, &mut self.new_food_tags);
+        allergens_editor(ui, "Contains allergens:", &mut self.new_food_allergens);
+
+        ui.label("Notes:");
+        ui.text_edit_multiline(&mut self.new_food_notes);
+
+        ui.checkbox(&mut self.share_with_household, "Share with household");
+
+        if let Some(name) = &self.duplicate_warning {
+            ui.label(egui::RichText::new(format!(
+                "A food named '{}' already exists. Press Save again to add another one.", name
+            )).color(egui::Color32::YELLOW));
+        }
 
         if ui.button("Save").clicked() {
-            let keywords = self.new_food_keywords.split(',').map(|s| s.trim().to_string()).collect();
-            let calories = self.new_food_calories.parse().unwrap_or(0.0);
+            let name = self.new_food_name.trim().to_string();
+            let brand = Some(self.new_food_brand.trim().to_string()).filter(|s| !s.is_empty());
+            let duplicate = db.basic_foods.values().any(|f| {
+                f.name == name && f.brand == brand && db.food_visible_to(&f.id, &db.current_user)
+            });
+            let calories = parse_calories(&self.new_food_calories);
 
-            let food = BasicFood {
-                id: self.new_food_id.clone(),
-                name: self.new_food_id.clone(), // Use identifier as name for simplicity
-                keywords,
-                calories_per_serving: calories,
-            };
+            if let Err(e) = validate_food_name(&name) {
+                self.error_message = Some(e);
+            } else if let Err(e) = &calories {
+                self.error_message = Some(e.clone());
+            } else if duplicate && self.duplicate_warning.as_ref() != Some(&name) {
+                self.duplicate_warning = Some(name);
+            } else {
+                self.duplicate_warning = None;
+                self.error_message = None;
 
-            db.basic_foods.insert(self.new_food_id.clone(), food);
-            *current_state = AppState::Home;
+
+                let food = BasicFood {
+                    id: BasicFood::new_id(),
+                    name,
+                    keywords: split_keywords(&self.new_food_keywords),
+                    calories_per_serving: calories.unwrap_or(0.0),
+                    macros: self.new_food_macros,
+                    fluid_ml: self.new_food_fluid_ml,
+                    version: 1,
+                    brand,
+                    description: Some(self.new_food_description.trim().to_string()).filter(|s| !s.is_empty()),
+                    notes: self.new_food_notes.clone(),
+                    category: self.new_food_category.clone(),
+                    tags: self.new_food_tags.clone(),
+                    allergens: self.new_food_allergens.clone(),
+                    owner: db.current_user.clone(),
+                    visibility: if self.share_with_household { Visibility::Shared } else { Visibility::Private },
+                };
+
+                db.save_basic_food(food);
+                *self = Self::new();
+                *current_state = AppState::Home;
+            }
         }
 
         if ui.button("Cancel").clicked() {
+            self.duplicate_warning = None;
+            self.error_message = None;
             *current_state = AppState::Home;
         }
     }
-}
\ No newline at end of file
+}
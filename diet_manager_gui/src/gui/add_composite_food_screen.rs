@@ -1,12 +1,22 @@
 use eframe::egui;
-use crate::models::{Database, CompositeFood, FoodComponent};
+use diet_core::models::{Database, CompositeFood, FoodComponent, FoodTag, RecipeYield, Visibility};
+use diet_core::services::{check_composite_food, split_keywords};
 use crate::app_state::AppState;
+use crate::gui::{category_and_tags_editor, components_editor, recipe_yield_editor};
 
 pub struct AddCompositeFoodScreen {
     new_food_id: String,
     new_food_name: String,
     new_food_keywords: String,
     selected_components: Vec<FoodComponent>,
+    new_component_id: String,
+    recipe_yield: RecipeYield,
+    raw_weight_g: Option<f32>,
+    category: Option<String>,
+    tags: Vec<FoodTag>,
+    share_with_household: bool,
+    overwrite_warning: Option<String>, // Id the user was warned about; saving it again overwrites
+    error_message: Option<String>,
 }
 
 impl AddCompositeFoodScreen {
@@ -16,12 +26,24 @@ impl AddCompositeFoodScreen {
             new_food_name: String::new(),
             new_food_keywords: String::new(),
             selected_components: Vec::new(),
+            new_component_id: String::new(),
+            recipe_yield: RecipeYield::default(),
+            raw_weight_g: None,
+            category: None,
+            tags: Vec::new(),
+            share_with_household: true,
+            overwrite_warning: None,
+            error_message: None,
         }
     }
 
     pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState) {
         ui.heading("Add Composite Food");
 
+        if let Some(error) = &self.error_message {
+            ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+        }
+
         ui.horizontal(|ui| {
             ui.label("Food Identifier:");
             ui.text_edit_singleline(&mut self.new_food_id);
@@ -37,28 +59,53 @@ impl AddCompositeFoodScreen {
             ui.text_edit_singleline(&mut self.new_food_keywords);
         });
 
-        if ui.button("Add Component").clicked() {
-            let food_id = "example_food_id".to_string(); // Replace with actual selection logic
-            let servings = 1.0; // Replace with actual input logic
-            self.selected_components.push(FoodComponent { food_id, servings });
+        // Identifiers are scoped to the current user so they only collide with the user's own foods
+        let key = Database::food_key(&db.current_user, self.new_food_id.trim());
+        let exclude = Some(key.as_str()).filter(|_| !self.new_food_id.trim().is_empty());
+        components_editor(ui, db, "add_composite_component", exclude, &mut self.selected_components, &mut self.new_component_id);
+        recipe_yield_editor(ui, &mut self.recipe_yield, &mut self.raw_weight_g);
+        category_and_tags_editor(ui, db, "add_composite_food_category", &mut self.category, &mut self.tags);
+        ui.checkbox(&mut self.share_with_household, "Share with household");
+
+        if let Some(id) = &self.overwrite_warning {
+            ui.label(egui::RichText::new(format!(
+                "A food with identifier '{}' already exists. Press Save again to overwrite it.", id
+            )).color(egui::Color32::YELLOW));
         }
 
         if ui.button("Save").clicked() {
-            let keywords = self.new_food_keywords.split(',').map(|s| s.trim().to_string()).collect();
-
-            let food = CompositeFood {
-                id: self.new_food_id.clone(),
+            let food = check_composite_food(CompositeFood {
+                id: key.clone(),
                 name: self.new_food_name.clone(),
-                keywords,
+                keywords: split_keywords(&self.new_food_keywords),
                 components: self.selected_components.clone(),
-            };
+                version: 1,
+                recipe_yield: self.recipe_yield.clone(),
+                raw_weight_g: self.raw_weight_g,
+                category: self.category.clone(),
+                tags: self.tags.clone(),
+                owner: db.current_user.clone(),
+                visibility: if self.share_with_household { Visibility::Shared } else { Visibility::Private },
+            });
 
-            db.composite_foods.insert(self.new_food_id.clone(), food);
-            *current_state = AppState::Home;
+            match food {
+                Err(e) => self.error_message = Some(e),
+                Ok(_) if db.food_exists(&key) && self.overwrite_warning.as_ref() != Some(&self.new_food_id) => {
+                    self.error_message = None;
+                    self.overwrite_warning = Some(self.new_food_id.clone());
+                }
+                Ok(food) => {
+                    db.save_composite_food(food);
+                    *self = Self::new();
+                    *current_state = AppState::Home;
+                }
+            }
         }
 
         if ui.button("Cancel").clicked() {
+            self.overwrite_warning = None;
+            self.error_message = None;
             *current_state = AppState::Home;
         }
     }
-}
\ No newline at end of file
+}
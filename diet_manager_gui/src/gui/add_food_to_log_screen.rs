@@ -1,26 +1,75 @@
 use eframe::egui;
-use crate::models::{Database, FoodLogEntry};
+use diet_core::models::{Database, FoodLogEntry, FoodTag, Macros, MealType, RestrictionPolicy};
+use diet_core::search::search_foods;
+use diet_core::services::log_entry;
+use diet_core::validation::validate_time;
 use crate::app_state::AppState;
+use crate::gui::{category_picker, macros_editor};
 
 pub struct AddFoodToLogScreen {
     selected_food_id: String,
     servings: f32,
     keywords: String,
     match_all_keywords: bool,
+    category_filter: Option<String>,
+    tag_filter: Vec<FoodTag>, // Foods must carry all of these tags
     selected_date: String, // ISO 8601 date format (e.g., "2023-10-01")
+    meal: MealType,
+    time: String, // "HH:MM"; empty to log without a time
+    quick_label: String,
+    quick_calories: String,
+    quick_macros: Option<Macros>,
+    quick_error: Option<String>,
+    error_message: Option<String>,
+    search_key: Option<SearchKey>, // What search_results were found for
+    search_results: Vec<String>,
+}
+
+/// The inputs of a search, to tell when its results are out of date.
+#[derive(PartialEq)]
+struct SearchKey {
+    query: String,
+    match_all: bool,
+    user_id: String,
+    catalog_version: u64,
+    log_len: usize, // Logging changes which foods are recent
 }
 
 impl AddFoodToLogScreen {
     pub fn new() -> Self {
+        let now = chrono::Local::now().format("%H:%M").to_string();
         Self {
             selected_food_id: String::new(),
             servings: 1.0,
             keywords: String::new(),
             match_all_keywords: true,
+            category_filter: None,
+            tag_filter: Vec::new(),
             selected_date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            meal: MealType::for_time(&now),
+            time: now,
+            quick_label: String::new(),
+            quick_calories: String::new(),
+            quick_macros: None,
+            quick_error: None,
+            error_message: None,
+            search_key: None,
+            search_results: Vec::new(),
         }
     }
 
+    /// Called when the screen is opened: a new entry defaults to today, the current time
+    /// and the meal for that time.
+    pub fn start(&mut self) {
+        let now = chrono::Local::now();
+        self.selected_date = now.format("%Y-%m-%d").to_string();
+        self.time = now.format("%H:%M").to_string();
+        self.meal = MealType::for_time(&self.time);
+        self.error_message = None;
+        self.quick_error = None;
+        self.search_key = None;
+    }
+
     pub fn render(
         &mut self,
         ui: &mut egui::Ui,
@@ -32,77 +81,203 @@ impl AddFoodToLogScreen {
         // Date selection
         ui.label("Select Date:");
         ui.text_edit_singleline(&mut self.selected_date);
-        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
-        if self.selected_date > today {
+        if self.selected_date > diet_core::services::today() {
             ui.label(egui::RichText::new("Cannot select a future date.").color(egui::Color32::RED));
         }
 
         // Display selected item
         if !self.selected_food_id.is_empty() {
-            ui.label(format!("Selected Food: {}", self.selected_food_id));
+            ui.label(format!("Selected Food: {}", db.food_display_name(&self.selected_food_id)));
+        }
+
+        // Allergen and dietary restriction check for the selected item
+        let conflicts = if self.selected_food_id.is_empty() {
+            Vec::new()
+        } else {
+            db.restriction_conflicts(&db.current_user, &self.selected_food_id)
+        };
+        let blocked = conflicts.iter().any(|c| c.blocks()) && db.user_by_id(&db.current_user)
+            .is_some_and(|user| user.profile.restriction_policy == RestrictionPolicy::Block);
+        if !conflicts.is_empty() {
+            let reasons: Vec<String> = conflicts.iter().map(|c| c.describe()).collect();
+            let text = format!("⚠ This food {}.", reasons.join(", "));
+            if blocked {
+                ui.label(egui::RichText::new(format!("{} It is blocked by your dietary restrictions.", text)).color(egui::Color32::RED));
+            } else {
+                ui.label(egui::RichText::new(text).color(egui::Color32::YELLOW));
+            }
         }
 
-        // Keyword filtering
-        ui.label("Filter by Keywords:");
+        // Search over names, brands and keywords
+        ui.label("Search:");
         ui.text_edit_singleline(&mut self.keywords);
-        ui.checkbox(&mut self.match_all_keywords, "Match all keywords");
+        ui.checkbox(&mut self.match_all_keywords, "Match all words");
 
-        // Food selection
-        ui.label("Select Food:");
-        let keywords: Vec<&str> = self.keywords.split_whitespace().collect();
-        for (id, food) in &db.basic_foods {
-            if self.matches_keywords(&food.keywords, &keywords) {
-                if ui.button(&food.name).clicked() {
-                    self.selected_food_id = id.clone();
+        // Category and tag filtering
+        ui.horizontal(|ui| {
+            ui.label("Category:");
+            category_picker(ui, db, "add_food_to_log_category", &mut self.category_filter, "All categories");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Tags:");
+            for tag in FoodTag::ALL {
+                let mut required = self.tag_filter.contains(&tag);
+                if ui.checkbox(&mut required, tag.label()).changed() {
+                    if required {
+                        self.tag_filter.push(tag);
+                    } else {
+                        self.tag_filter.retain(|t| *t != tag);
+                    }
                 }
             }
+        });
+
+        // Food selection
+        ui.label("Select Food:");
+        if self.keywords.trim().is_empty() {
+            ui.label("Recently logged foods; type to search all foods.");
         }
-        for (id, food) in &db.composite_foods {
-            if self.matches_keywords(&food.keywords, &keywords) {
-                if ui.button(&food.name).clicked() {
-                    self.selected_food_id = id.clone();
-                }
+        self.refresh_search(db);
+        let mut clicked = None;
+        for id in &self.search_results {
+            if self.matches_filters(db, id) && ui.button(self.food_label(db, id, db.food_display_name(id))).clicked() {
+                clicked = Some(id.clone());
             }
         }
+        if let Some(id) = clicked {
+            self.selected_food_id = id;
+        }
 
         // Servings input
         ui.label("Servings:");
-        ui.add(egui::Slider::new(&mut self.servings, 0.1..=10.0));
+        ui.add(egui::DragValue::new(&mut self.servings).speed(0.05).clamp_range(0.01..=f32::MAX));
+
+        // Meal and time of day
+        ui.horizontal(|ui| {
+            ui.label("Meal:");
+            for meal in MealType::ALL {
+                ui.radio_value(&mut self.meal, meal, meal.label());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Time (HH:MM, optional):");
+            ui.text_edit_singleline(&mut self.time);
+        });
+        let time = validate_time(&self.time);
+        if let Err(e) = &time {
+            ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+        }
+        let time = time.unwrap_or(None);
+        let fasting = db.user_by_id(&db.current_user).and_then(|user| user.profile.fasting.clone());
+        if let (Some(schedule), Some(time)) = (&fasting, &time) {
+            if schedule.in_window(time) == Some(false) {
+                ui.label(egui::RichText::new(format!(
+                    "⚠ {} is outside your eating window ({}).", time, schedule.label()
+                )).color(egui::Color32::YELLOW));
+            }
+        }
 
         // Add to log button
         if ui.button("Add to Log").clicked() {
-            if self.selected_food_id.is_empty() || self.servings <= 0.0 {
-                ui.label(egui::RichText::new("Please select a food and enter a valid serving size.").color(egui::Color32::RED));
-            } else if self.selected_date > today {
-                ui.label(egui::RichText::new("Cannot add to a future date.").color(egui::Color32::RED));
+            if self.selected_food_id.is_empty() {
+                self.error_message = Some("Please select a food and enter a valid serving size.".to_string());
             } else {
-                let entry = FoodLogEntry {
-                    date: self.selected_date.clone(),
-                    food_id: self.selected_food_id.clone(),
-                    servings: self.servings,
-                    user_id: db.current_user.clone(), // Add user_id to entry
-                };
-                db.food_logs
-                    .entry(db.current_user.clone())
-                    .or_insert_with(Vec::new)
-                    .push(entry);
-                *current_state = AppState::Home; // Return to home screen
+                let mut entry = FoodLogEntry::new(&db.current_user, &self.selected_date, &self.selected_food_id, self.servings);
+                entry.meal = self.meal;
+                entry.time = time.clone();
+                match log_entry(db, entry) {
+                    Ok(_) => {
+                        self.selected_food_id.clear();
+                        self.servings = 1.0;
+                        self.error_message = None;
+                        *current_state = AppState::Home;
+                    }
+                    Err(e) => self.error_message = Some(e),
+                }
             }
         }
+        if let Some(error) = &self.error_message {
+            ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+        }
+
+        // Quick add: raw calories without creating a food first
+        ui.separator();
+        ui.collapsing("Quick Add Calories", |ui| {
+            if let Some(error) = &self.quick_error {
+                ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+            }
+            ui.horizontal(|ui| {
+                ui.label("Label:");
+                ui.text_edit_singleline(&mut self.quick_label);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Calories:");
+                ui.text_edit_singleline(&mut self.quick_calories);
+            });
+
+            macros_editor(ui, &mut self.quick_macros);
+
+            if ui.button("Quick Add").clicked() {
+                let mut entry = FoodLogEntry::quick(
+                    &db.current_user,
+                    &self.selected_date,
+                    self.quick_label.trim(),
+                    self.quick_calories.trim().parse().unwrap_or(0.0),
+                    self.quick_macros,
+                );
+                entry.meal = self.meal;
+                entry.time = time.clone();
+                match log_entry(db, entry) {
+                    Ok(_) => {
+                        self.quick_label.clear();
+                        self.quick_calories.clear();
+                        self.quick_macros = None;
+                        self.quick_error = None;
+                        *current_state = AppState::Home;
+                    }
+                    Err(e) => self.quick_error = Some(e),
+                }
+            }
+        });
     }
 
-    fn matches_keywords(&self, food_keywords: &[String], filter_keywords: &[&str]) -> bool {
-        if filter_keywords.is_empty() {
-            return true;
+    /// Searches again only when the query, the user, the foods or the user's log changed.
+    fn refresh_search(&mut self, db: &Database) {
+        let key = SearchKey {
+            query: self.keywords.clone(),
+            match_all: self.match_all_keywords,
+            user_id: db.current_user.clone(),
+            catalog_version: db.food_catalog_version(),
+            log_len: db.food_logs.get(&db.current_user).map_or(0, Vec::len),
+        };
+        if self.search_key.as_ref() != Some(&key) {
+            self.search_results = search_foods(db, &db.current_user, &self.keywords, self.match_all_keywords);
+            self.search_key = Some(key);
         }
-        if self.match_all_keywords {
-            filter_keywords
-                .iter()
-                .all(|kw| food_keywords.iter().any(|fk| fk.contains(kw)))
+    }
+
+    /// Button text for a food, flagged when it conflicts with the user's restrictions.
+    fn food_label(&self, db: &Database, food_id: &str, name: String) -> String {
+        if db.restriction_conflicts(&db.current_user, food_id).is_empty() {
+            name
         } else {
-            filter_keywords
-                .iter()
-                .any(|kw| food_keywords.iter().any(|fk| fk.contains(kw)))
+            format!("{} ⚠", name)
+        }
+    }
+
+    fn matches_filters(&self, db: &Database, food_id: &str) -> bool {
+        if !db.food_visible_to(food_id, &db.current_user) {
+            return false;
+        }
+        if let Some(category) = &self.category_filter {
+            if !db.food_in_category(food_id, category) {
+                return false;
+            }
+        }
+        if self.tag_filter.is_empty() {
+            return true;
         }
+        let tags = db.effective_tags(food_id);
+        self.tag_filter.iter().all(|tag| tags.contains(tag))
     }
 }
\ No newline at end of file
@@ -0,0 +1,55 @@
+use eframe::egui;
+use diet_core::models::Database;
+use crate::app_state::AppState;
+
+pub struct AnnotationReportScreen;
+
+impl AnnotationReportScreen {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState) {
+        ui.heading("Hunger & Mood Report");
+
+        let report = db.annotation_report(&db.current_user);
+
+        ui.separator();
+        ui.label(egui::RichText::new("Hunger before eating").strong());
+        if report.by_hunger.is_empty() {
+            ui.label("No entries with a hunger level yet.");
+        }
+        for (level, average, count) in &report.by_hunger {
+            ui.label(format!("Hunger {}/5: {:.1} kcal per entry ({} entries)", level, average, count));
+        }
+        if let Some(r) = report.hunger_correlation {
+            ui.label(format!("Correlation with calories: {:.2} ({})", r, describe_correlation(r)));
+        }
+
+        ui.separator();
+        ui.label(egui::RichText::new("How I felt afterwards").strong());
+        if report.by_mood.is_empty() {
+            ui.label("No entries with a mood yet.");
+        }
+        for (level, average, count) in &report.by_mood {
+            ui.label(format!("Mood {}/5: {:.1} kcal per entry ({} entries)", level, average, count));
+        }
+        if let Some(r) = report.mood_correlation {
+            ui.label(format!("Correlation with calories: {:.2} ({})", r, describe_correlation(r)));
+        }
+
+        ui.separator();
+        if ui.button("Back").clicked() {
+            *current_state = AppState::Home;
+        }
+    }
+}
+
+fn describe_correlation(r: f32) -> &'static str {
+    match r.abs() {
+        a if a < 0.1 => "none",
+        a if a < 0.3 => if r > 0.0 { "weak, larger meals" } else { "weak, smaller meals" },
+        a if a < 0.5 => if r > 0.0 { "moderate, larger meals" } else { "moderate, smaller meals" },
+        _ => if r > 0.0 { "strong, larger meals" } else { "strong, smaller meals" },
+    }
+}
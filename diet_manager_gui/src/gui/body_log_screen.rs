@@ -0,0 +1,139 @@
+use eframe::egui;
+use diet_core::models::{Database, WeightEntry};
+use diet_core::reports::weight_moving_average;
+use diet_core::services::{log_weight, today};
+use crate::app_state::AppState;
+
+const TREND_DAYS: i64 = 7;
+
+pub struct BodyLogScreen {
+    date: String,
+    weight_kg: f32,
+    waist_cm: Option<f32>,
+    body_fat_percent: Option<f32>,
+    error_message: Option<String>,
+}
+
+impl BodyLogScreen {
+    pub fn new() -> Self {
+        Self {
+            date: today(),
+            weight_kg: 0.0, // Filled in from the latest entry when the screen is first shown
+            waist_cm: None,
+            body_fat_percent: None,
+            error_message: None,
+        }
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState) {
+        ui.heading("Weight & Measurements");
+        let current_user = db.current_user.clone();
+
+        if self.weight_kg == 0.0 {
+            self.weight_kg = db.latest_weight(&current_user).map(|entry| entry.weight_kg)
+                .or_else(|| db.user_by_id(&current_user).map(|user| user.profile.weight_kg))
+                .unwrap_or(70.0);
+        }
+
+        if let Some(error) = &self.error_message {
+            ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+        }
+        ui.horizontal(|ui| {
+            ui.label("Date (YYYY-MM-DD):");
+            ui.add(egui::TextEdit::singleline(&mut self.date).desired_width(90.0));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Weight (kg):");
+            ui.add(egui::DragValue::new(&mut self.weight_kg).speed(0.1).clamp_range(1.0..=500.0));
+        });
+        optional_value(ui, "Waist (cm)", &mut self.waist_cm, 80.0, 1.0..=300.0);
+        optional_value(ui, "Body fat (%)", &mut self.body_fat_percent, 25.0, 1.0..=75.0);
+
+        if ui.button("Save").clicked() {
+            let entry = WeightEntry {
+                date: self.date.trim().to_string(),
+                weight_kg: self.weight_kg,
+                waist_cm: self.waist_cm,
+                body_fat_percent: self.body_fat_percent,
+            };
+            match log_weight(db, &current_user, entry) {
+                Ok(()) => {
+                    self.error_message = None;
+                    self.date = today();
+                }
+                Err(e) => self.error_message = Some(e),
+            }
+        }
+
+        if db.user_by_id(&current_user).is_some_and(|user| user.profile.weight_from_log) {
+            ui.label("Your latest entry sets the weight used for your calorie target.");
+        } else {
+            ui.label(egui::RichText::new("Turn on \"Use my latest logged weight\" in Update Profile to base your calorie target on this log.").weak());
+        }
+
+        ui.separator();
+        let entries = db.weight_history(&current_user).to_vec();
+        if entries.is_empty() {
+            ui.label("No entries yet.");
+        } else {
+            // Trend: the latest moving average, and how it moved over the last 30 days
+            let trend = weight_moving_average(&entries, TREND_DAYS);
+            if let Some((date, average)) = trend.last() {
+                ui.label(format!("{}-day average: {:.1} kg", TREND_DAYS, average));
+                let month_ago = *date - chrono::Duration::days(30);
+                if let Some((_, earlier)) = trend.iter().rev().find(|(d, _)| *d <= month_ago) {
+                    ui.label(format!("Change over 30 days: {:+.1} kg", average - earlier));
+                }
+            }
+
+            let mut to_delete = None;
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                egui::Grid::new("body_log").striped(true).show(ui, |ui| {
+                    for heading in ["Date", "Weight", "Trend", "Waist", "Body fat", ""] {
+                        ui.label(egui::RichText::new(heading).strong());
+                    }
+                    ui.end_row();
+                    for (entry, (_, average)) in entries.iter().zip(&trend).rev() {
+                        ui.label(&entry.date);
+                        ui.label(format!("{:.1} kg", entry.weight_kg));
+                        ui.label(format!("{:.1} kg", average));
+                        ui.label(entry.waist_cm.map_or(String::new(), |waist| format!("{:.1} cm", waist)));
+                        ui.label(entry.body_fat_percent.map_or(String::new(), |fat| format!("{:.1}%", fat)));
+                        if ui.button("Delete").clicked() {
+                            to_delete = Some(entry.date.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+            if let Some(date) = to_delete {
+                db.delete_weight_entry(&current_user, &date);
+            }
+        }
+
+        ui.separator();
+        if ui.button("Back").clicked() {
+            *self = Self::new();
+            *current_state = AppState::Home;
+        }
+    }
+}
+
+/// A checkbox that enables a number field, for measurements that are not always taken.
+fn optional_value(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut Option<f32>,
+    default: f32,
+    range: std::ops::RangeInclusive<f32>,
+) {
+    ui.horizontal(|ui| {
+        let mut enabled = value.is_some();
+        if ui.checkbox(&mut enabled, format!("{}:", label)).changed() {
+            *value = if enabled { Some(default) } else { None };
+        }
+        if let Some(value) = value {
+            ui.add(egui::DragValue::new(value).speed(0.1).clamp_range(range));
+        }
+    });
+}
@@ -0,0 +1,170 @@
+use eframe::egui;
+use egui::plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints, Points};
+use chrono::{Duration, NaiveDate};
+use diet_core::models::Database;
+use diet_core::reports::{daily_totals, day_macros, weight_moving_average};
+use crate::app_state::AppState;
+
+const RANGES: [(i64, &str); 4] = [(7, "7 days"), (30, "30 days"), (90, "90 days"), (365, "Year")];
+
+const PROTEIN_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 90, 90);
+const CARBS_COLOR: egui::Color32 = egui::Color32::from_rgb(90, 150, 220);
+const FAT_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 190, 70);
+
+pub struct ChartsScreen {
+    days: i64, // Length of the range, ending on end_date
+    end_date: NaiveDate,
+    pie_date: NaiveDate, // Day shown in the macro pie chart
+}
+
+impl ChartsScreen {
+    pub fn new() -> Self {
+        let today = chrono::Local::now().date_naive();
+        Self {
+            days: 30,
+            end_date: today,
+            pie_date: today,
+        }
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState) {
+        ui.heading("Charts");
+        let today = chrono::Local::now().date_naive();
+
+        ui.horizontal(|ui| {
+            for (days, label) in RANGES {
+                ui.radio_value(&mut self.days, days, label);
+            }
+        });
+        let start = self.end_date - Duration::days(self.days - 1);
+        ui.horizontal(|ui| {
+            if ui.button("◄").clicked() {
+                self.end_date = start - Duration::days(1);
+            }
+            ui.label(format!("{} to {}", start.format("%Y-%m-%d"), self.end_date.format("%Y-%m-%d")));
+            if ui.add_enabled(self.end_date < today, egui::Button::new("►")).clicked() {
+                self.end_date = (self.end_date + Duration::days(self.days)).min(today);
+            }
+        });
+        let start = self.end_date - Duration::days(self.days - 1);
+
+        // x is the number of days since the start of the range
+        let x_of = move |date: NaiveDate| (date - start).num_days() as f64;
+        let date_label = move |x: f64, _: &std::ops::RangeInclusive<f64>| {
+            if x.fract() != 0.0 {
+                return String::new();
+            }
+            (start + Duration::days(x as i64)).format("%m-%d").to_string()
+        };
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.separator();
+            ui.label(egui::RichText::new("Calories consumed and target").strong());
+            let days = daily_totals(db, &db.current_user, start, self.end_date);
+            let bars: Vec<Bar> = days.iter()
+                .filter(|day| day.logged)
+                .map(|day| {
+                    let color = if day.consumed > day.target { egui::Color32::RED } else { egui::Color32::GREEN };
+                    Bar::new(x_of(day.date), day.consumed as f64).fill(color.gamma_multiply(0.6))
+                })
+                .collect();
+            let target: PlotPoints = days.iter().map(|day| [x_of(day.date), day.target as f64]).collect();
+            Plot::new("calories_chart")
+                .height(200.0)
+                .legend(Legend::default())
+                .include_y(0.0)
+                .x_axis_formatter(date_label)
+                .allow_scroll(false)
+                .show(ui, |plot_ui| {
+                    plot_ui.bar_chart(BarChart::new(bars).width(0.8).name("Consumed"));
+                    plot_ui.line(Line::new(target).color(egui::Color32::LIGHT_BLUE).name("Target"));
+                });
+
+            ui.separator();
+            ui.label(egui::RichText::new("Weight (kg)").strong());
+            let range = start.format("%Y-%m-%d").to_string()..=self.end_date.format("%Y-%m-%d").to_string();
+            let history = db.weight_history(&db.current_user);
+            let weights: Vec<[f64; 2]> = history.iter()
+                .filter(|entry| range.contains(&entry.date))
+                .filter_map(|entry| {
+                    let date = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d").ok()?;
+                    Some([x_of(date), entry.weight_kg as f64])
+                })
+                .collect();
+            let trend: PlotPoints = weight_moving_average(history, 7).into_iter()
+                .filter(|(date, _)| *date >= start && *date <= self.end_date)
+                .map(|(date, average)| [x_of(date), average as f64])
+                .collect();
+            if weights.is_empty() {
+                ui.label("No weights recorded in this range. Add them under Weight & Measurements.");
+            } else {
+                Plot::new("weight_chart")
+                    .height(160.0)
+                    .x_axis_formatter(date_label)
+                    .include_x(0.0)
+                    .include_x((self.days - 1) as f64)
+                    .allow_scroll(false)
+                    .legend(Legend::default())
+                    .show(ui, |plot_ui| {
+                        plot_ui.points(Points::new(weights).radius(3.0).name("Weight"));
+                        plot_ui.line(Line::new(trend).name("7-day average"));
+                    });
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Macros on").strong());
+                if ui.button("◄").clicked() {
+                    self.pie_date -= Duration::days(1);
+                }
+                ui.label(self.pie_date.format("%Y-%m-%d").to_string());
+                if ui.add_enabled(self.pie_date < today, egui::Button::new("►")).clicked() {
+                    self.pie_date += Duration::days(1);
+                }
+            });
+            let macros = day_macros(db, &db.current_user, self.pie_date);
+            match macros.energy_split() {
+                Some((protein, carbs, fat)) => {
+                    ui.horizontal(|ui| {
+                        pie_chart(ui, &[(protein, PROTEIN_COLOR), (carbs, CARBS_COLOR), (fat, FAT_COLOR)]);
+                        ui.vertical(|ui| {
+                            ui.colored_label(PROTEIN_COLOR, format!("Protein {:.0}% ({:.0} g)", protein, macros.protein_g));
+                            ui.colored_label(CARBS_COLOR, format!("Carbs {:.0}% ({:.0} g)", carbs, macros.carbs_g));
+                            ui.colored_label(FAT_COLOR, format!("Fat {:.0}% ({:.0} g)", fat, macros.fat_g));
+                        });
+                    });
+                }
+                None => {
+                    ui.label("No foods with macros logged on this day.");
+                }
+            }
+        });
+
+        ui.separator();
+        if ui.button("Back").clicked() {
+            *current_state = AppState::Home;
+        }
+    }
+}
+
+/// Draws a pie of the given shares (in any unit) with the painter. Each slice is drawn as a
+/// fan of thin triangles, since a slice over half the pie is not a convex shape.
+fn pie_chart(ui: &mut egui::Ui, slices: &[(f32, egui::Color32)]) {
+    let (response, painter) = ui.allocate_painter(egui::Vec2::splat(120.0), egui::Sense::hover());
+    let center = response.rect.center();
+    let radius = response.rect.width() / 2.0 - 2.0;
+    let total: f32 = slices.iter().map(|(share, _)| share).sum();
+    let point = |angle: f32| center + radius * egui::Vec2::angled(angle - std::f32::consts::FRAC_PI_2);
+
+    let mut angle = 0.0;
+    for (share, color) in slices {
+        let sweep = share / total * std::f32::consts::TAU;
+        let steps = (sweep / 0.1).ceil().max(1.0) as usize;
+        for step in 0..steps {
+            let from = angle + sweep * step as f32 / steps as f32;
+            let to = angle + sweep * (step + 1) as f32 / steps as f32;
+            painter.add(egui::Shape::convex_polygon(vec![center, point(from), point(to)], *color, egui::Stroke::NONE));
+        }
+        angle += sweep;
+    }
+}
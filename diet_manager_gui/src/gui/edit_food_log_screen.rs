@@ -1,15 +1,31 @@
 use eframe::egui;
-use crate::models::Database;
+use diet_core::models::{Database, FoodLogEntry, MealType};
+use diet_core::services::update_entry;
+use diet_core::validation::validate_time;
 use crate::app_state::AppState;
 
 pub struct EditFoodLogScreen {
     selected_date: String,
+    annotating: Option<String>, // Id of the entry whose annotations are being edited
+    note: String,
+    photo_path: String,
+    hunger: u8, // 0 means not recorded
+    mood: u8,   // 0 means not recorded
+    time_draft: Option<(String, String)>, // Entry id and the time being typed for it
+    error_message: Option<String>,
 }
 
 impl EditFoodLogScreen {
     pub fn new() -> Self {
         Self {
             selected_date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            annotating: None,
+            note: String::new(),
+            photo_path: String::new(),
+            hunger: 0,
+            mood: 0,
+            time_draft: None,
+            error_message: None,
         }
     }
 
@@ -21,33 +37,158 @@ impl EditFoodLogScreen {
     ) {
         ui.heading("Edit Food Log");
 
+        if let Some(error) = &self.error_message {
+            ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+        }
+
         // Date selection
         ui.label("Select Date:");
         ui.text_edit_singleline(&mut self.selected_date);
 
-        // Display food entries for the selected date
-        if let Some(entries) = db.food_logs.get_mut(&db.current_user) { // Use get_mut here
-            let mut to_remove = Vec::new();
-            for (index, entry) in entries.iter_mut().enumerate() {
-                if entry.date == self.selected_date {
-                    ui.horizontal(|ui| {
-                        ui.label(&entry.food_id);
-                        ui.add(egui::Slider::new(&mut entry.servings, 0.1..=10.0).text("Servings"));
-                        if ui.button("Delete").clicked() {
-                            to_remove.push(index);
+        // Work on copies and write changes back by entry id
+        let entries: Vec<FoodLogEntry> = db.log_entries_for(&db.current_user, &self.selected_date)
+            .into_iter()
+            .cloned()
+            .collect();
+
+        let mut updated = Vec::new();
+        let mut deleted = Vec::new();
+        for mut entry in entries {
+            ui.horizontal(|ui| {
+                ui.label(db.entry_display_name(&entry));
+                let mut changed = ui.add(egui::DragValue::new(&mut entry.servings).speed(0.05).clamp_range(0.01..=f32::MAX).suffix(" servings")).changed();
+                egui::ComboBox::from_id_source(format!("edit_meal_{}", entry.id))
+                    .selected_text(entry.meal.label())
+                    .show_ui(ui, |ui| {
+                        for meal in MealType::ALL {
+                            changed |= ui.selectable_value(&mut entry.meal, meal, meal.label()).changed();
                         }
                     });
+                changed |= self.time_editor(ui, &mut entry);
+                if changed {
+                    updated.push(entry.clone());
                 }
+                if ui.button("Annotate").clicked() {
+                    self.start_annotating(&entry);
+                }
+                if ui.button("Delete").clicked() {
+                    deleted.push(entry.id.clone());
+                }
+            });
+        }
+
+        for entry in updated {
+            self.error_message = update_entry(db, entry).err();
+        }
+        let current_user = db.current_user.clone();
+        for entry_id in deleted {
+            if self.annotating.as_ref() == Some(&entry_id) {
+                self.annotating = None;
             }
-            // Remove deleted entries
-            for index in to_remove.into_iter().rev() {
-                entries.remove(index);
-            }
+            db.delete_log_entry(&current_user, &entry_id);
+        }
+
+        if let Some(entry_id) = self.annotating.clone() {
+            ui.separator();
+            self.render_annotation_editor(ui, db, &entry_id);
         }
 
         // Back button
         if ui.button("Back").clicked() {
+            self.annotating = None;
+            self.time_draft = None;
+            self.error_message = None;
             *current_state = AppState::Home;
         }
     }
-}
\ No newline at end of file
+
+    /// Time field of an entry. The typed text is kept until the field loses focus and is only
+    /// written to the entry when valid. Returns whether the entry's time changed.
+    fn time_editor(&mut self, ui: &mut egui::Ui, entry: &mut FoodLogEntry) -> bool {
+        let mut text = match &self.time_draft {
+            Some((id, text)) if *id == entry.id => text.clone(),
+            _ => entry.time.clone().unwrap_or_default(),
+        };
+        let response = ui.add(egui::TextEdit::singleline(&mut text).desired_width(48.0).hint_text("HH:MM"));
+        if response.changed() {
+            self.time_draft = Some((entry.id.clone(), text.clone()));
+        }
+        let editing = matches!(&self.time_draft, Some((id, _)) if *id == entry.id);
+        if !(editing && response.lost_focus()) {
+            return false;
+        }
+        self.time_draft = None;
+        match validate_time(&text) {
+            Ok(time) if time != entry.time => {
+                entry.time = time;
+                true
+            }
+            Ok(_) => false,
+            Err(e) => {
+                self.error_message = Some(e);
+                false
+            }
+        }
+    }
+
+    fn start_annotating(&mut self, entry: &FoodLogEntry) {
+        self.annotating = Some(entry.id.clone());
+        self.note = entry.note.clone().unwrap_or_default();
+        self.photo_path = entry.photo_path.clone().unwrap_or_default();
+        self.hunger = entry.hunger.unwrap_or(0);
+        self.mood = entry.mood.unwrap_or(0);
+    }
+
+    fn render_annotation_editor(&mut self, ui: &mut egui::Ui, db: &mut Database, entry_id: &str) {
+        let Some(entry) = db.find_log_entry(&db.current_user, entry_id).cloned() else {
+            self.annotating = None;
+            return;
+        };
+
+        ui.label(format!("Annotations for {}", db.entry_display_name(&entry)));
+
+        ui.label("Note:");
+        ui.text_edit_multiline(&mut self.note);
+
+        ui.horizontal(|ui| {
+            ui.label("Photo path:");
+            ui.text_edit_singleline(&mut self.photo_path);
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Hunger before eating:");
+            ui.radio_value(&mut self.hunger, 0, "-");
+            for level in 1..=5 {
+                ui.radio_value(&mut self.hunger, level, level.to_string());
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("How I felt afterwards:");
+            ui.radio_value(&mut self.mood, 0, "-");
+            for level in 1..=5 {
+                ui.radio_value(&mut self.mood, level, level.to_string());
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("Save Annotations").clicked() {
+                let mut entry = entry.clone();
+                entry.note = Some(self.note.trim().to_string()).filter(|s| !s.is_empty());
+                entry.photo_path = Some(self.photo_path.trim().to_string()).filter(|s| !s.is_empty());
+                entry.hunger = Some(self.hunger).filter(|level| *level > 0);
+                entry.mood = Some(self.mood).filter(|level| *level > 0);
+                match update_entry(db, entry) {
+                    Ok(()) => {
+                        self.error_message = None;
+                        self.annotating = None;
+                    }
+                    Err(e) => self.error_message = Some(e),
+                }
+            }
+            if ui.button("Cancel").clicked() {
+                self.annotating = None;
+            }
+        });
+    }
+}
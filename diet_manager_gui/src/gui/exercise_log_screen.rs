@@ -0,0 +1,146 @@
+use eframe::egui;
+use chrono::NaiveDate;
+use diet_core::models::{Activity, Database, ExerciseEntry, Intensity};
+use diet_core::services::log_exercise;
+use crate::app_state::AppState;
+
+pub struct ExerciseLogScreen {
+    selected_date: NaiveDate,
+    activity: Activity,
+    intensity: Intensity,
+    duration_min: f32,
+    editing: Option<String>, // Id of the entry being edited; None adds a new one
+    error_message: Option<String>,
+}
+
+impl ExerciseLogScreen {
+    pub fn new() -> Self {
+        Self {
+            selected_date: chrono::Local::now().date_naive(),
+            activity: Activity::Walking,
+            intensity: Intensity::Moderate,
+            duration_min: 30.0,
+            editing: None,
+            error_message: None,
+        }
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState) {
+        ui.heading("Exercise Log");
+        let current_user = db.current_user.clone();
+
+        ui.horizontal(|ui| {
+            ui.label("Date:");
+            if ui.button("◄").clicked() {
+                self.selected_date -= chrono::Duration::days(1);
+                self.editing = None;
+            }
+            ui.label(self.selected_date.format("%Y-%m-%d").to_string());
+            if ui.button("►").clicked() {
+                let tomorrow = self.selected_date.succ_opt().unwrap_or(self.selected_date);
+                if tomorrow <= chrono::Local::now().date_naive() {
+                    self.selected_date = tomorrow;
+                    self.editing = None;
+                }
+            }
+        });
+        let date = self.selected_date.format("%Y-%m-%d").to_string();
+
+        if let Some(error) = &self.error_message {
+            ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+        }
+        ui.horizontal(|ui| {
+            ui.label("Activity:");
+            egui::ComboBox::from_id_source("exercise_activity")
+                .selected_text(self.activity.label())
+                .show_ui(ui, |ui| {
+                    for activity in Activity::ALL {
+                        ui.selectable_value(&mut self.activity, activity, activity.label());
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Intensity:");
+            for intensity in Intensity::ALL {
+                ui.radio_value(&mut self.intensity, intensity, intensity.label());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Duration (minutes):");
+            ui.add(egui::DragValue::new(&mut self.duration_min).speed(1.0).clamp_range(1.0..=600.0));
+        });
+
+        let weight_kg = db.weight_on(&current_user, &date);
+        let mut preview = ExerciseEntry::new(&current_user, &date, self.activity, self.intensity, self.duration_min);
+        ui.label(format!(
+            "About {:.0} kcal ({} MET at {:.1} kg)",
+            preview.estimate_calories(weight_kg),
+            self.activity.met(self.intensity),
+            weight_kg
+        ));
+
+        ui.horizontal(|ui| {
+            let save_label = if self.editing.is_some() { "Update" } else { "Add" };
+            if ui.button(save_label).clicked() {
+                if let Some(id) = &self.editing {
+                    preview.id = id.clone();
+                }
+                match log_exercise(db, preview) {
+                    Ok(_) => {
+                        self.editing = None;
+                        self.error_message = None;
+                    }
+                    Err(e) => self.error_message = Some(e),
+                }
+            }
+            if self.editing.is_some() && ui.button("Cancel Edit").clicked() {
+                self.editing = None;
+            }
+        });
+
+        ui.separator();
+        let entries: Vec<ExerciseEntry> = db.exercise_entries_for(&current_user, &date).into_iter().cloned().collect();
+        if entries.is_empty() {
+            ui.label("No exercise logged on this date.");
+        }
+        for entry in &entries {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{} ({}) - {:.0} min, {:.0} kcal",
+                    entry.activity.label(),
+                    entry.intensity.label().to_lowercase(),
+                    entry.duration_min,
+                    entry.calories_burned
+                ));
+                if ui.button("Edit").clicked() {
+                    self.activity = entry.activity;
+                    self.intensity = entry.intensity;
+                    self.duration_min = entry.duration_min;
+                    self.editing = Some(entry.id.clone());
+                }
+                if ui.button("Delete").clicked() {
+                    db.delete_exercise_entry(&current_user, &entry.id);
+                    if self.editing.as_ref() == Some(&entry.id) {
+                        self.editing = None;
+                    }
+                }
+            });
+        }
+
+        let summary = db.calculate_calories(&current_user, &date);
+        if summary.burned > 0.0 {
+            let eaten_back = db.user_by_id(&current_user).is_some_and(|user| user.profile.eat_back_exercise);
+            ui.label(format!(
+                "Burned {:.0} kcal in total; {}",
+                summary.burned,
+                if eaten_back { "added to your daily goal." } else { "not added to your daily goal (see Update Profile)." }
+            ));
+        }
+
+        ui.separator();
+        if ui.button("Back").clicked() {
+            *self = Self::new();
+            *current_state = AppState::Home;
+        }
+    }
+}
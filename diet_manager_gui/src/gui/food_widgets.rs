@@ -0,0 +1,201 @@
+use eframe::egui;
+use diet_core::models::{Allergen, Database, FoodComponent, FoodTag, Macros, RecipeYield};
+
+/// Lists the components of a composite food with editable servings, plus a picker to add more.
+/// Foods that contain `exclude` are not offered, so a recipe can never contain itself.
+pub fn components_editor(
+    ui: &mut egui::Ui,
+    db: &Database,
+    id_source: &str,
+    exclude: Option<&str>,
+    components: &mut Vec<FoodComponent>,
+    new_component_id: &mut String,
+) {
+    ui.label("Components:");
+    let mut to_remove = None;
+    for (index, component) in components.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(db.food_display_name(&component.food_id));
+            ui.add(egui::DragValue::new(&mut component.servings).speed(0.05).clamp_range(0.01..=f32::MAX).suffix(" servings"));
+            if ui.button("❌").clicked() {
+                to_remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = to_remove {
+        components.remove(index);
+    }
+
+    ui.horizontal(|ui| {
+        let mut candidates: Vec<(String, &String)> = db.basic_foods.keys()
+            .chain(db.composite_foods.keys())
+            .filter(|candidate| db.food_visible_to(candidate, &db.current_user))
+            .filter(|candidate| exclude.is_none_or(|id| !db.contains_food(candidate, id)))
+            .map(|candidate| (db.food_display_name(candidate), candidate))
+            .collect();
+        candidates.sort();
+        let selected_text = if new_component_id.is_empty() {
+            String::new()
+        } else {
+            db.food_display_name(new_component_id)
+        };
+        egui::ComboBox::from_id_source(id_source)
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for (name, candidate) in candidates {
+                    ui.selectable_value(new_component_id, candidate.clone(), name);
+                }
+            });
+        if ui.button("Add Component").clicked() && !new_component_id.is_empty() {
+            components.push(FoodComponent {
+                food_id: new_component_id.clone(),
+                servings: 1.0,
+            });
+        }
+    });
+}
+
+/// Edits how many portions a recipe makes, either as a count or as cooked weight.
+pub fn recipe_yield_editor(ui: &mut egui::Ui, recipe_yield: &mut RecipeYield, raw_weight_g: &mut Option<f32>) {
+    ui.horizontal(|ui| {
+        ui.label("Recipe Yield:");
+        let by_portions = matches!(recipe_yield, RecipeYield::Portions(_));
+        if ui.radio(by_portions, "Portions").clicked() && !by_portions {
+            *recipe_yield = RecipeYield::Portions(recipe_yield.portions());
+        }
+        if ui.radio(!by_portions, "Cooked weight").clicked() && by_portions {
+            *recipe_yield = RecipeYield::CookedWeight { total_g: 100.0 * recipe_yield.portions(), portion_g: 100.0 };
+        }
+    });
+
+    match recipe_yield {
+        RecipeYield::Portions(portions) => {
+            ui.horizontal(|ui| {
+                ui.label("Number of portions:");
+                ui.add(egui::DragValue::new(portions).speed(0.1).clamp_range(0.1..=100.0));
+            });
+        }
+        RecipeYield::CookedWeight { total_g, portion_g } => {
+            ui.horizontal(|ui| {
+                ui.label("Cooked total (g):");
+                ui.add(egui::DragValue::new(total_g).speed(5.0).clamp_range(1.0..=20000.0));
+                ui.label("Portion size (g):");
+                ui.add(egui::DragValue::new(portion_g).speed(5.0).clamp_range(1.0..=5000.0));
+            });
+        }
+    }
+
+    ui.horizontal(|ui| {
+        let mut has_raw_weight = raw_weight_g.is_some();
+        if ui.checkbox(&mut has_raw_weight, "Record raw weight (g):").changed() {
+            *raw_weight_g = if has_raw_weight { Some(0.0) } else { None };
+        }
+        if let Some(raw_g) = raw_weight_g {
+            ui.add(egui::DragValue::new(raw_g).speed(5.0).clamp_range(0.0..=20000.0));
+        }
+    });
+
+    ui.label(format!("Makes {:.1} portion(s).", recipe_yield.portions()));
+}
+
+/// Picks a category from the tree and toggles the typed tags of a food.
+pub fn category_and_tags_editor(
+    ui: &mut egui::Ui,
+    db: &Database,
+    id_source: &str,
+    category: &mut Option<String>,
+    tags: &mut Vec<FoodTag>,
+) {
+    ui.horizontal(|ui| {
+        ui.label("Category:");
+        category_picker(ui, db, id_source, category, "None");
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Tags:");
+        for tag in FoodTag::ALL {
+            let mut has_tag = tags.contains(&tag);
+            if ui.checkbox(&mut has_tag, tag.label()).changed() {
+                if has_tag {
+                    tags.push(tag);
+                    tags.sort();
+                } else {
+                    tags.retain(|t| *t != tag);
+                }
+            }
+        }
+    });
+}
+
+/// Combo box over all categories, shown by their full path. `none_label` names the empty choice.
+pub fn category_picker(
+    ui: &mut egui::Ui,
+    db: &Database,
+    id_source: &str,
+    category: &mut Option<String>,
+    none_label: &str,
+) {
+    let mut categories: Vec<(String, String)> = db.categories.keys()
+        .map(|id| (db.category_path(id), id.clone()))
+        .collect();
+    categories.sort();
+    let selected_text = category.as_ref()
+        .map_or(none_label.to_string(), |id| db.category_path(id));
+    egui::ComboBox::from_id_source(id_source)
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(category, None, none_label);
+            for (path, id) in categories {
+                ui.selectable_value(category, Some(id), path);
+            }
+        });
+}
+
+/// Checkbox grid over the major allergens.
+pub fn allergens_editor(ui: &mut egui::Ui, label: &str, allergens: &mut Vec<Allergen>) {
+    ui.label(label);
+    ui.horizontal_wrapped(|ui| {
+        for allergen in Allergen::ALL {
+            let mut present = allergens.contains(&allergen);
+            if ui.checkbox(&mut present, allergen.label()).changed() {
+                if present {
+                    allergens.push(allergen);
+                    allergens.sort();
+                } else {
+                    allergens.retain(|a| *a != allergen);
+                }
+            }
+        }
+    });
+}
+
+/// Optional protein, carbs and fat in grams, behind an "Include macros" checkbox.
+pub fn macros_editor(ui: &mut egui::Ui, macros: &mut Option<Macros>) {
+    let mut with_macros = macros.is_some();
+    if ui.checkbox(&mut with_macros, "Include macros").changed() {
+        *macros = if with_macros { Some(Macros::default()) } else { None };
+    }
+    if let Some(macros) = macros {
+        ui.horizontal(|ui| {
+            ui.label("Protein (g):");
+            ui.add(egui::DragValue::new(&mut macros.protein_g).clamp_range(0.0..=1000.0));
+            ui.label("Carbs (g):");
+            ui.add(egui::DragValue::new(&mut macros.carbs_g).clamp_range(0.0..=1000.0));
+            ui.label("Fat (g):");
+            ui.add(egui::DragValue::new(&mut macros.fat_g).clamp_range(0.0..=1000.0));
+        });
+    }
+}
+
+/// Fluid per serving for drinks, behind a "Beverage" checkbox. Beverages count toward hydration.
+pub fn beverage_editor(ui: &mut egui::Ui, fluid_ml: &mut Option<f32>) {
+    ui.horizontal(|ui| {
+        let mut beverage = fluid_ml.is_some();
+        if ui.checkbox(&mut beverage, "Beverage, fluid per serving (ml):").changed() {
+            *fluid_ml = if beverage { Some(250.0) } else { None };
+        }
+        if let Some(fluid_ml) = fluid_ml {
+            ui.add(egui::DragValue::new(fluid_ml).speed(5.0).clamp_range(1.0..=5000.0));
+        }
+    });
+}
@@ -1,7 +1,9 @@
 use eframe::egui;
-use crate::models::Database;
+use diet_core::models::{Database, FastingPhase, WaterEntry};
+use diet_core::reports::current_fasting_streak;
+use diet_core::services::log_water;
 use crate::app_state::AppState;
-use crate::gui::undo_manager::UndoManager;
+use diet_core::undo::UndoManager;
 
 pub struct HomeScreen;
 
@@ -27,6 +29,9 @@ impl HomeScreen {
         if ui.button("Add Composite Food").clicked() {
             *current_state = AppState::AddCompositeFood;
         }
+        if ui.button("Manage Foods").clicked() {
+            *current_state = AppState::ManageFoods;
+        }
         if ui.button("View Daily Log").clicked() {
             *current_state = AppState::ViewDailyLog;
         }
@@ -36,9 +41,24 @@ impl HomeScreen {
         if ui.button("Edit Food Log").clicked() {
             *current_state = AppState::EditFoodLog;
         }
+        if ui.button("Exercise Log").clicked() {
+            *current_state = AppState::ExerciseLog;
+        }
         if ui.button("Update Profile").clicked() {
             *current_state = AppState::UpdateProfile;
         }
+        if ui.button("Weight & Measurements").clicked() {
+            *current_state = AppState::BodyLog;
+        }
+        if ui.button("Reports").clicked() {
+            *current_state = AppState::Reports;
+        }
+        if ui.button("Charts").clicked() {
+            *current_state = AppState::Charts;
+        }
+        if ui.button("Hunger & Mood Report").clicked() {
+            *current_state = AppState::AnnotationReport;
+        }
 
         // Logout button
         if ui.button("Logout").clicked() {
@@ -46,66 +66,128 @@ impl HomeScreen {
             *current_state = AppState::Login;
         }
 
-        // Undo button
-        if ui.button("Undo").clicked() {
-            if let Some(previous_db_state) = undo_manager.undo() {
-                *db = previous_db_state;
+        // Undo and redo of the current user's own actions (also Ctrl+Z / Ctrl+Shift+Z)
+        let current_user = db.current_user.clone();
+        ui.horizontal(|ui| {
+            let next_undo = undo_manager.undo_labels(&current_user).first().map(|l| l.to_string());
+            let undo = ui.add_enabled(next_undo.is_some(), egui::Button::new("Undo"));
+            if let Some(label) = &next_undo {
+                if undo.on_hover_text(format!("Undo {}", label)).clicked() {
+                    undo_manager.undo(&current_user, db);
+                }
             }
+            let next_redo = undo_manager.redo_labels(&current_user).first().map(|l| l.to_string());
+            let redo = ui.add_enabled(next_redo.is_some(), egui::Button::new("Redo"));
+            if let Some(label) = &next_redo {
+                if redo.on_hover_text(format!("Redo {}", label)).clicked() {
+                    undo_manager.redo(&current_user, db);
+                }
+            }
+        });
+        if let Some(status) = undo_manager.status(&current_user) {
+            ui.label(status);
         }
+        ui.collapsing("Undo History", |ui| {
+            let mut persist = undo_manager.is_persisted(&current_user);
+            if ui.checkbox(&mut persist, "Keep undo history after exit").changed() {
+                undo_manager.set_persisted(&current_user, persist);
+            }
+            for label in undo_manager.redo_labels(&current_user).into_iter().rev() {
+                ui.label(egui::RichText::new(format!("↷ {}", label)).weak());
+            }
+            for label in undo_manager.undo_labels(&current_user) {
+                ui.label(format!("↶ {}", label));
+            }
+        });
 
         // Calculate and display calorie information for current user
         if !db.current_user.is_empty() {
             let date = chrono::Local::now().format("%Y-%m-%d").to_string();
             
             // Calculate calories using user_id directly
-            let (total_calories, target_calories, difference) = self.calculate_user_calories(db, &db.current_user, &date);
+            let summary = db.calculate_calories(&db.current_user, &date);
             
             ui.separator();
             ui.heading("Daily Nutrition Summary");
-            ui.label(format!("Total Calories Consumed: {:.2}", total_calories));
-            ui.label(format!("Calories Goal: {:.2}", target_calories));
+            ui.label(format!("Total Calories Consumed: {:.2}", summary.consumed));
+            if summary.burned > 0.0 {
+                ui.label(format!("Burned by Exercise: {:.2}", summary.burned));
+            }
+            if summary.target != summary.base_target {
+                ui.label(format!("Calories Goal: {:.2} ({:.2} + {:.2} eaten back)", summary.target, summary.base_target, summary.burned));
+            } else {
+                ui.label(format!("Calories Goal: {:.2}", summary.target));
+            }
             
-            if difference >= 0.0 {
-                ui.label(egui::RichText::new(format!("Remaining: {:.2}", difference)).color(egui::Color32::GREEN));
+            if summary.remaining >= 0.0 {
+                ui.label(egui::RichText::new(format!("Remaining: {:.2}", summary.remaining)).color(egui::Color32::GREEN));
             } else {
-                ui.label(egui::RichText::new(format!("Over by: {:.2}", difference.abs())).color(egui::Color32::RED));
+                ui.label(egui::RichText::new(format!("Over by: {:.2}", summary.remaining.abs())).color(egui::Color32::RED));
             }
 
-            if target_calories > 0.0 {
-                let progress = total_calories / target_calories;
+            if summary.target > 0.0 {
+                let progress = summary.consumed / summary.target;
                 ui.add(egui::ProgressBar::new(progress.clamp(0.0, 1.0)).text(format!(
                     "{:.1}% of daily goal", 
                     progress * 100.0
                 )));
             }
+
+            // Hydration: water and beverages against a goal from body weight
+            let fluid = db.fluid_for(&db.current_user, &date);
+            let goal = db.user_by_id(&db.current_user).map_or(0.0, |user| user.profile.water_goal_ml());
+            ui.separator();
+            ui.heading("Hydration");
+            ui.horizontal(|ui| {
+                ui.label(format!("{:.0} of {:.0} ml", fluid, goal));
+                for amount in [250.0, 500.0] {
+                    if ui.button(format!("+{:.0} ml", amount)).clicked() {
+                        let mut entry = WaterEntry::new(&db.current_user, &date, amount);
+                        entry.time = Some(chrono::Local::now().format("%H:%M").to_string());
+                        let _ = log_water(db, entry);
+                    }
+                }
+            });
+            if goal > 0.0 {
+                ui.add(egui::ProgressBar::new((fluid / goal).clamp(0.0, 1.0)));
+            }
+
+            // Live fasting timer
+            if let Some(schedule) = db.user_by_id(&db.current_user).and_then(|user| user.profile.fasting.clone()) {
+                let now = chrono::Local::now().naive_local();
+                ui.separator();
+                ui.heading(format!("Fasting ({})", schedule.label()));
+                let (since, until) = match schedule.phase_at(now) {
+                    FastingPhase::Eating { since, until } => {
+                        ui.label(egui::RichText::new(format!(
+                            "Eating window open - closes at {} (in {})", until.format("%H:%M"), format_span(until - now)
+                        )).color(egui::Color32::GREEN));
+                        (since, until)
+                    }
+                    FastingPhase::Fasting { since, until } => {
+                        ui.label(format!(
+                            "Fasting for {} - eating window opens at {} (in {})",
+                            format_span(now - since), until.format("%H:%M"), format_span(until - now)
+                        ));
+                        (since, until)
+                    }
+                };
+                let progress = (now - since).num_seconds() as f32 / (until - since).num_seconds().max(1) as f32;
+                ui.add(egui::ProgressBar::new(progress.clamp(0.0, 1.0)));
+                let streak = current_fasting_streak(db, &db.current_user, now.date());
+                if streak > 0 {
+                    ui.label(format!("Streak: {} day(s) within your eating window", streak));
+                }
+                ui.ctx().request_repaint_after(std::time::Duration::from_secs(1));
+            }
         } else {
             ui.label("No user logged in");
         }
     }
+}
 
-    fn calculate_user_calories(
-        &self,
-        db: &Database,
-        user_id: &str,
-        date: &str
-    ) -> (f32, f32, f32) {
-        // Calculate total calories consumed from food logs
-        let total_calories = db.food_logs
-            .get(user_id)
-            .map_or(0.0, |entries| {
-                entries.iter()
-                    .filter(|entry| entry.date == date)
-                    .map(|entry| db.get_food_calories(&entry.food_id).unwrap_or(0.0) * entry.servings)
-                    .sum()
-            });
-
-        // Get target calories from user profile
-        let target_calories = db.users.values()
-            .find(|u| u.user_id == user_id)
-            .map(|user| user.profile.calculate_target_calories())
-            .unwrap_or(0.0);
-
-        let difference = target_calories - total_calories;
-        (total_calories, target_calories, difference)
-    }
-}
\ No newline at end of file
+/// A duration as "H:MM:SS", for the fasting timer.
+fn format_span(span: chrono::Duration) -> String {
+    let seconds = span.num_seconds().max(0);
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
@@ -1,8 +1,9 @@
 // src/gui/login_screen.rs
 use eframe::egui;
-use crate::models::Database;
+use diet_core::models::Database;
+use diet_core::services::login;
+use diet_core::storage::save_database;
 use crate::app_state::AppState;
-use std::fs;
 
 pub struct LoginScreen {
     username: String,
@@ -37,24 +38,18 @@ impl LoginScreen {
         });
 
         if ui.button("Login").clicked() {
-            if let Some(user) = db.users.get(&self.username) {
-                if user.password == self.password {
-                    // Store user_id instead of username
-                    db.current_user = user.user_id.clone();
-                    
+            match login(db, &self.username, &self.password) {
+                Ok(_) => {
                     // Save the database to JSON file
-                    if let Err(e) = self.save_database(db) {
+                    if let Err(e) = save_database(db) {
                         self.error_message = Some(format!("Failed to save login state: {}", e));
                         return;
                     }
                     
                     *current_state = AppState::Home;
                     self.error_message = None;
-                } else {
-                    self.error_message = Some("Incorrect password.".to_string());
                 }
-            } else {
-                self.error_message = Some("User not found.".to_string());
+                Err(e) => self.error_message = Some(e),
             }
         }
 
@@ -62,10 +57,4 @@ impl LoginScreen {
             *current_state = AppState::Register;
         }
     }
-
-    fn save_database(&self, db: &Database) -> Result<(), Box<dyn std::error::Error>> {
-        let json = serde_json::to_string_pretty(db)?;
-        fs::write("database.json", json)?;
-        Ok(())
-    }
 }
\ No newline at end of file
@@ -0,0 +1,412 @@
+use eframe::egui;
+use diet_core::models::{Allergen, Database, FoodComponent, DeleteImpact, FoodTag, Macros, RecipeYield, Visibility};
+use diet_core::services::{check_composite_food, split_keywords};
+use diet_core::validation::{parse_calories, validate_food_name};
+use crate::app_state::AppState;
+use crate::gui::{allergens_editor, beverage_editor, category_and_tags_editor, category_picker, components_editor, macros_editor, recipe_yield_editor};
+
+pub struct ManageFoodsScreen {
+    selected_food_id: Option<String>,
+    edit_name: String,
+    edit_brand: String, // Only used for basic foods
+    edit_description: String, // Only used for basic foods
+    edit_notes: String, // Only used for basic foods
+    edit_keywords: String,
+    edit_calories: String, // Only used for basic foods
+    edit_macros: Option<Macros>, // Only used for basic foods
+    edit_fluid_ml: Option<f32>, // Only used for basic foods
+    edit_components: Vec<FoodComponent>, // Only used for composite foods
+    edit_yield: RecipeYield,
+    edit_raw_weight_g: Option<f32>,
+    scale_factor: f32,
+    edit_category: Option<String>,
+    edit_tags: Vec<FoodTag>,
+    edit_allergens: Vec<Allergen>, // Only used for basic foods
+    new_category_name: String,
+    new_category_parent: Option<String>,
+    new_component_id: String,
+    reapply_to_history: bool,
+    edit_shared: bool,
+    copy_requested: Option<String>, // Shared composite food to copy into the user's catalog
+    pending_delete: Option<(String, DeleteImpact)>, // Food awaiting cascade confirmation
+    message: Option<String>,
+}
+
+impl ManageFoodsScreen {
+    pub fn new() -> Self {
+        Self {
+            selected_food_id: None,
+            edit_name: String::new(),
+            edit_brand: String::new(),
+            edit_description: String::new(),
+            edit_notes: String::new(),
+            edit_keywords: String::new(),
+            edit_calories: String::new(),
+            edit_macros: None,
+            edit_fluid_ml: None,
+            edit_components: Vec::new(),
+            edit_yield: RecipeYield::default(),
+            edit_raw_weight_g: None,
+            scale_factor: 1.0,
+            edit_category: None,
+            edit_tags: Vec::new(),
+            edit_allergens: Vec::new(),
+            new_category_name: String::new(),
+            new_category_parent: None,
+            new_component_id: String::new(),
+            reapply_to_history: false,
+            edit_shared: false,
+            copy_requested: None,
+            pending_delete: None,
+            message: None,
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        ui: &mut egui::Ui,
+        db: &mut Database,
+        current_state: &mut AppState,
+    ) {
+        ui.heading("Manage Foods");
+
+        if let Some(message) = &self.message {
+            ui.label(message);
+        }
+
+        egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+            ui.label("Basic Foods:");
+            let mut basic_foods: Vec<_> = db.basic_foods.values()
+                .filter(|food| db.food_visible_to(&food.id, &db.current_user))
+                .collect();
+            basic_foods.sort_by_key(|food| food.display_name());
+            for food in basic_foods {
+                let label = format!("{} - {:.1} kcal", food.display_name(), food.calories_per_serving);
+                self.food_row(ui, db, &food.id, label);
+            }
+
+            ui.separator();
+            ui.label("Composite Foods:");
+            let mut composite_foods: Vec<_> = db.composite_foods.values()
+                .filter(|food| db.food_visible_to(&food.id, &db.current_user))
+                .collect();
+            composite_foods.sort_by_key(|food| food.name.clone());
+            for food in composite_foods {
+                let id = &food.id;
+                let calories = db.get_food_calories(id).unwrap_or(0.0);
+                let mut label = format!(
+                    "{} ({}) - {:.1} kcal per portion, {:.1} portions",
+                    food.name, food.local_id(), calories, food.recipe_yield.portions()
+                );
+                if let Some(loss) = food.cooking_loss_percent() {
+                    label.push_str(&format!(", {:.0}% cooking loss", loss));
+                }
+                self.food_row(ui, db, id, label);
+            }
+        });
+
+        if let Some(id) = self.copy_requested.take() {
+            let current_user = db.current_user.clone();
+            match db.copy_composite_food(&id, &current_user) {
+                Ok(_) => self.message = Some(format!("Copied '{}' into your foods.", db.food_display_name(&id))),
+                Err(e) => self.message = Some(e),
+            }
+        }
+
+        if let Some((id, impact)) = self.pending_delete.clone() {
+            let name = db.food_display_name(&id);
+            ui.separator();
+            if impact.is_empty() {
+                ui.label(format!("Delete '{}'?", name));
+            } else {
+                let names: Vec<String> = impact.composite_foods.iter().map(|id| db.food_display_name(id)).collect();
+                ui.label(egui::RichText::new(format!(
+                    "'{}' is used by {} composite food(s) [{}] and {} log entry(ies), counting everything that contains it.",
+                    name,
+                    impact.composite_foods.len(),
+                    names.join(", "),
+                    impact.log_entries
+                )).color(egui::Color32::YELLOW));
+                if impact.others > 0 {
+                    ui.label(egui::RichText::new(format!(
+                        "{} of them belong to other users, so it cannot be deleted.", impact.others
+                    )).color(egui::Color32::RED));
+                } else {
+                    ui.label("Deleting it will also delete those composite foods and log entries.");
+                }
+            }
+            ui.horizontal(|ui| {
+                if impact.others == 0 && ui.button("Confirm Delete").clicked() {
+                    let current_user = db.current_user.clone();
+                    match db.delete_food(&id, &current_user, true) {
+                        Ok(()) => self.message = Some(format!("Deleted '{}' and everything using it.", name)),
+                        Err(e) => self.message = Some(e),
+                    }
+                    if self.selected_food_id.as_ref().is_some_and(|selected| !db.food_exists(selected)) {
+                        self.selected_food_id = None;
+                    }
+                    self.pending_delete = None;
+                }
+                if ui.button("Cancel").clicked() {
+                    self.pending_delete = None;
+                }
+            });
+        }
+
+        if let Some(id) = self.selected_food_id.clone() {
+            ui.separator();
+            self.render_editor(ui, db, &id);
+        }
+
+        ui.separator();
+        self.render_categories(ui, db);
+
+        ui.separator();
+        if ui.button("Back").clicked() {
+            self.selected_food_id = None;
+            self.pending_delete = None;
+            self.message = None;
+            *current_state = AppState::Home;
+        }
+    }
+
+    fn food_row(&mut self, ui: &mut egui::Ui, db: &Database, id: &str, label: String) {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            if db.food_editable_by(id, &db.current_user) {
+                if ui.button("Edit").clicked() {
+                    self.select_food(db, id);
+                }
+                if ui.button("Delete").clicked() {
+                    self.pending_delete = Some((id.to_string(), db.delete_impact(id, &db.current_user)));
+                }
+            } else {
+                let owner = db.food_owner(id)
+                    .and_then(|owner| db.user_by_id(owner))
+                    .map_or("another user".to_string(), |user| user.username.clone());
+                ui.label(format!("[shared by {}]", owner));
+                if db.composite_foods.contains_key(id) && ui.button("Copy").clicked() {
+                    self.copy_requested = Some(id.to_string());
+                }
+            }
+        });
+    }
+
+    fn select_food(&mut self, db: &Database, id: &str) {
+        self.selected_food_id = Some(id.to_string());
+        self.new_component_id.clear();
+        self.reapply_to_history = false;
+        self.message = None;
+        self.edit_shared = db.basic_foods.get(id).map(|f| f.visibility)
+            .or_else(|| db.composite_foods.get(id).map(|f| f.visibility))
+            == Some(Visibility::Shared);
+        self.edit_category = db.basic_foods.get(id).and_then(|f| f.category.clone())
+            .or_else(|| db.composite_foods.get(id).and_then(|f| f.category.clone()));
+        self.edit_tags = db.basic_foods.get(id).map(|f| f.tags.clone())
+            .or_else(|| db.composite_foods.get(id).map(|f| f.tags.clone()))
+            .unwrap_or_default();
+        if let Some(food) = db.basic_foods.get(id) {
+            self.edit_name = food.name.clone();
+            self.edit_brand = food.brand.clone().unwrap_or_default();
+            self.edit_description = food.description.clone().unwrap_or_default();
+            self.edit_notes = food.notes.clone();
+            self.edit_keywords = food.keywords.join(", ");
+            self.edit_calories = food.calories_per_serving.to_string();
+            self.edit_macros = food.macros;
+            self.edit_fluid_ml = food.fluid_ml;
+            self.edit_allergens = food.allergens.clone();
+            self.edit_components.clear();
+        } else if let Some(food) = db.composite_foods.get(id) {
+            self.edit_name = food.name.clone();
+            self.edit_keywords = food.keywords.join(", ");
+            self.edit_calories.clear();
+            self.edit_components = food.components.clone();
+            self.edit_yield = food.recipe_yield.clone();
+            self.edit_raw_weight_g = food.raw_weight_g;
+            self.scale_factor = 1.0;
+        }
+    }
+
+    fn render_editor(&mut self, ui: &mut egui::Ui, db: &mut Database, id: &str) {
+        let is_basic = db.basic_foods.contains_key(id);
+        let version = db.current_food_version(id).unwrap_or(1);
+        ui.heading(format!("Edit '{}' (version {})", db.food_display_name(id), version));
+
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            ui.text_edit_singleline(&mut self.edit_name);
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Keywords (comma-separated):");
+            ui.text_edit_singleline(&mut self.edit_keywords);
+        });
+
+        if is_basic {
+            ui.horizontal(|ui| {
+                ui.label("Brand:");
+                ui.text_edit_singleline(&mut self.edit_brand);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Description:");
+                ui.text_edit_singleline(&mut self.edit_description);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Calories:");
+                ui.text_edit_singleline(&mut self.edit_calories);
+            });
+            macros_editor(ui, &mut self.edit_macros);
+            beverage_editor(ui, &mut self.edit_fluid_ml);
+            ui.label("Notes:");
+            ui.text_edit_multiline(&mut self.edit_notes);
+        } else {
+            components_editor(ui, db, "manage_foods_component", Some(id), &mut self.edit_components, &mut self.new_component_id);
+            recipe_yield_editor(ui, &mut self.edit_yield, &mut self.edit_raw_weight_g);
+
+            ui.horizontal(|ui| {
+                ui.label("Scale recipe by:");
+                ui.add(egui::DragValue::new(&mut self.scale_factor).speed(0.1).clamp_range(0.1..=20.0));
+                if ui.button("Scale").clicked() {
+                    if let Some(mut scaled) = db.composite_foods.get(id).cloned() {
+                        scaled.components = self.edit_components.clone();
+                        scaled.recipe_yield = self.edit_yield.clone();
+                        scaled.raw_weight_g = self.edit_raw_weight_g;
+                        scaled.scale(self.scale_factor);
+                        self.edit_components = scaled.components;
+                        self.edit_yield = scaled.recipe_yield;
+                        self.edit_raw_weight_g = scaled.raw_weight_g;
+                    }
+                    self.scale_factor = 1.0;
+                }
+            });
+        }
+
+        category_and_tags_editor(ui, db, "manage_foods_category", &mut self.edit_category, &mut self.edit_tags);
+        if is_basic {
+            allergens_editor(ui, "Contains allergens:", &mut self.edit_allergens);
+        } else {
+            let allergens: Vec<&str> = db.food_allergens(id).iter().map(|a| a.label()).collect();
+            if !allergens.is_empty() {
+                ui.label(format!("Contains allergens: {}", allergens.join(", ")));
+            }
+            let inherited: Vec<&str> = db.effective_tags(id).iter()
+                .filter(|tag| !self.edit_tags.contains(tag))
+                .map(|tag| tag.label())
+                .collect();
+            if !inherited.is_empty() {
+                ui.label(format!("Inherited from components: {}", inherited.join(", ")));
+            }
+        }
+
+        ui.checkbox(&mut self.edit_shared, "Share with household");
+        ui.checkbox(&mut self.reapply_to_history, "Also correct past log entries (re-apply to history)");
+
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                let keywords = split_keywords(&self.edit_keywords);
+                let visibility = if self.edit_shared { Visibility::Shared } else { Visibility::Private };
+                let checked = validate_food_name(&self.edit_name)
+                    .and_then(|name| Ok((name, if is_basic { Some(parse_calories(&self.edit_calories)?) } else { None })));
+                let saved = match checked {
+                    Ok((name, Some(calories))) => {
+                        if let Some(mut food) = db.basic_foods.get(id).cloned() {
+                            food.name = name;
+                            food.brand = Some(self.edit_brand.trim().to_string()).filter(|s| !s.is_empty());
+                            food.description = Some(self.edit_description.trim().to_string()).filter(|s| !s.is_empty());
+                            food.notes = self.edit_notes.clone();
+                            food.keywords = keywords;
+                            food.calories_per_serving = calories;
+                            food.macros = self.edit_macros;
+                            food.fluid_ml = self.edit_fluid_ml;
+                            food.category = self.edit_category.clone();
+                            food.tags = self.edit_tags.clone();
+                            food.allergens = self.edit_allergens.clone();
+                            food.visibility = visibility;
+                            db.save_basic_food(food);
+                        }
+                        true
+                    }
+                    Ok((name, None)) => {
+                        let food = db.composite_foods.get(id).cloned().map(|mut food| {
+                            food.name = name;
+                            food.keywords = keywords;
+                            food.components = self.edit_components.clone();
+                            food.recipe_yield = self.edit_yield.clone();
+                            food.raw_weight_g = self.edit_raw_weight_g;
+                            food.category = self.edit_category.clone();
+                            food.tags = self.edit_tags.clone();
+                            food.visibility = visibility;
+                            check_composite_food(food)
+                        });
+                        match food {
+                            Some(Ok(food)) => {
+                                db.save_composite_food(food);
+                                true
+                            }
+                            Some(Err(e)) => {
+                                self.message = Some(e);
+                                false
+                            }
+                            None => true,
+                        }
+                    }
+                    Err(e) => {
+                        self.message = Some(e);
+                        false
+                    }
+                };
+
+                if saved {
+                    if self.reapply_to_history {
+                        let updated = db.reapply_food_to_history(id);
+                        self.message = Some(format!("Saved '{}' and updated {} past log entry(ies).", db.food_display_name(id), updated));
+                    } else {
+                        self.message = Some(format!("Saved '{}'.", db.food_display_name(id)));
+                    }
+                    self.selected_food_id = None;
+                }
+            }
+            if ui.button("Close").clicked() {
+                self.selected_food_id = None;
+            }
+        });
+    }
+
+    fn render_categories(&mut self, ui: &mut egui::Ui, db: &mut Database) {
+        ui.collapsing("Categories", |ui| {
+            let mut categories: Vec<(String, String)> = db.categories.keys()
+                .map(|id| (db.category_path(id), id.clone()))
+                .collect();
+            categories.sort();
+            let mut to_delete = None;
+            for (path, id) in categories {
+                ui.horizontal(|ui| {
+                    ui.label(path);
+                    if ui.button("❌").clicked() {
+                        to_delete = Some(id);
+                    }
+                });
+            }
+            if let Some(id) = to_delete {
+                db.delete_category(&id);
+                if self.edit_category.as_ref() == Some(&id) {
+                    self.edit_category = None;
+                }
+                if self.new_category_parent.as_ref() == Some(&id) {
+                    self.new_category_parent = None;
+                }
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("New category:");
+                ui.text_edit_singleline(&mut self.new_category_name);
+                ui.label("under");
+                category_picker(ui, db, "manage_foods_category_parent", &mut self.new_category_parent, "(top level)");
+                if ui.button("Add").clicked() && !self.new_category_name.trim().is_empty() {
+                    db.add_category(self.new_category_name.trim(), self.new_category_parent.clone());
+                    self.new_category_name.clear();
+                }
+            });
+        });
+    }
+}
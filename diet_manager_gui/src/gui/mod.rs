@@ -5,10 +5,16 @@ mod add_composite_food_screen;
 mod view_daily_log_screen;
 mod login_screen; // Add this line
 mod register_screen; // Add this line
-pub mod undo_manager; // Add this line
 mod add_food_to_log_screen; // Add this line
 mod edit_food_log_screen;   // Add this line
 mod update_profile_screen;  // Add this line
+mod manage_foods_screen;
+mod food_widgets;
+mod annotation_report_screen;
+mod reports_screen;
+mod charts_screen;
+mod body_log_screen;
+mod exercise_log_screen;
 
 
 pub use home_screen::*;
@@ -17,7 +23,13 @@ pub use add_composite_food_screen::*;
 pub use view_daily_log_screen::*;
 pub use login_screen::*; // Add this line
 pub use register_screen::*; // Add this line
-// pub use undo_manager::*; // Add this line
 pub use add_food_to_log_screen::*; // Add this line
 pub use edit_food_log_screen::*;   // Add this line
-pub use update_profile_screen::*;  // Add this line
\ No newline at end of file
+pub use update_profile_screen::*;  // Add this line
+pub use manage_foods_screen::*;
+pub use food_widgets::*;
+pub use annotation_report_screen::*;
+pub use reports_screen::*;
+pub use charts_screen::*;
+pub use body_log_screen::*;
+pub use exercise_log_screen::*;
\ No newline at end of file
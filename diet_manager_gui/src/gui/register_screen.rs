@@ -1,9 +1,9 @@
 // src/gui/register_screen.rs
 use eframe::egui;
-use crate::models::{Database, User, UserProfile, Gender, ActivityLevel, CalorieCalculationMethod};
+use diet_core::models::{Database, Gender, ActivityLevel, CalorieCalculationMethod};
+use diet_core::services::{register, NewUser};
+use diet_core::storage::save_database;
 use crate::app_state::AppState;
-use uuid::Uuid;
-use std::fs;
 
 pub struct RegisterScreen {
     username: String,
@@ -86,47 +86,29 @@ impl RegisterScreen {
         });
 
         if ui.button("Register").clicked() {
-            if self.username.is_empty() || self.password.is_empty() {
-                self.error_message = Some("Username and password are required.".to_string());
-            } else if db.users.contains_key(&self.username) {
-                self.error_message = Some("Username already exists.".to_string());
-            } else {
-                let height_cm = self.height_cm.parse().unwrap_or(0.0);
-                let age = self.age.parse().unwrap_or(0);
-                let weight_kg = self.weight_kg.parse().unwrap_or(0.0);
-
-                if height_cm <= 0.0 || age <= 0 || weight_kg <= 0.0 {
-                    self.error_message = Some("Invalid height, age, or weight.".to_string());
-                } else {
-                    let user_id = Uuid::new_v4().to_string();
-                    let profile = UserProfile {
-                        gender: self.gender.clone(),
-                        height_cm,
-                        age,
-                        calorie_method: self.calorie_method.clone(),
-                        weight_kg,
-                        activity_level: self.activity_level.clone(),
-                    };
-
-                    let user = User {
-                        user_id: user_id.clone(),
-                        username: self.username.clone(),
-                        password: self.password.clone(),
-                        profile,
-                    };
-
-                    // Insert new user and set as current user
-                    db.users.insert(self.username.clone(), user);
-                    db.current_user = user_id;
-                    
+            let new_user = NewUser {
+                username: self.username.clone(),
+                password: self.password.clone(),
+                gender: self.gender.clone(),
+                height_cm: self.height_cm.parse().unwrap_or(0.0),
+                age: self.age.parse().unwrap_or(0),
+                weight_kg: self.weight_kg.parse().unwrap_or(0.0),
+                activity_level: self.activity_level.clone(),
+                calorie_method: self.calorie_method.clone(),
+            };
+
+            // Creates the user and sets them as current user
+            match register(db, new_user) {
+                Ok(_) => {
                     // Save the database to JSON file
-                    if let Err(e) = self.save_database(db) {
+                    if let Err(e) = save_database(db) {
                         self.error_message = Some(format!("Failed to save registration: {}", e));
                         return;
                     }
                     
                     *current_state = AppState::Home;
                 }
+                Err(e) => self.error_message = Some(e),
             }
         }
 
@@ -134,10 +116,4 @@ impl RegisterScreen {
             *current_state = AppState::Login;
         }
     }
-
-    fn save_database(&self, db: &Database) -> Result<(), Box<dyn std::error::Error>> {
-        let json = serde_json::to_string_pretty(db)?;
-        fs::write("database.json", json)?;
-        Ok(())
-    }
 }
\ No newline at end of file
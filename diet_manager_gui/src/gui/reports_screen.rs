@@ -0,0 +1,184 @@
+use eframe::egui;
+use chrono::{Datelike, Duration, NaiveDate};
+use diet_core::models::Database;
+use diet_core::reports::{range_report, DayTotal, RangeReport, ReportPeriod};
+use diet_core::validation::validate_date;
+use crate::app_state::AppState;
+
+pub struct ReportsScreen {
+    period: Option<ReportPeriod>, // None for a custom range
+    end_date: NaiveDate, // Last day of the week or month shown
+    custom_start: String,
+    custom_end: String,
+    report: Option<(ReportKey, RangeReport)>, // Last report and what it was computed for
+}
+
+/// The inputs of a report, to tell when it is out of date.
+#[derive(PartialEq)]
+struct ReportKey {
+    start: NaiveDate,
+    end: NaiveDate,
+    user_id: String,
+    data_revision: u64,
+}
+
+impl ReportsScreen {
+    pub fn new() -> Self {
+        let today = chrono::Local::now().date_naive();
+        Self {
+            period: Some(ReportPeriod::Week),
+            end_date: today,
+            custom_start: (today - Duration::days(13)).format("%Y-%m-%d").to_string(),
+            custom_end: today.format("%Y-%m-%d").to_string(),
+            report: None,
+        }
+    }
+
+    pub fn render(&mut self, ui: &mut egui::Ui, db: &mut Database, current_state: &mut AppState) {
+        ui.heading("Nutrition Reports");
+        let today = chrono::Local::now().date_naive();
+
+        ui.horizontal(|ui| {
+            for period in [ReportPeriod::Week, ReportPeriod::Month] {
+                ui.radio_value(&mut self.period, Some(period), period.label());
+            }
+            ui.radio_value(&mut self.period, None, "Custom");
+        });
+
+        let range = match self.period {
+            Some(period) => {
+                let start = period.start(self.end_date);
+                ui.horizontal(|ui| {
+                    if ui.button("◄").clicked() {
+                        self.end_date = start - Duration::days(1);
+                    }
+                    ui.label(format!("{} to {}", start.format("%Y-%m-%d"), self.end_date.format("%Y-%m-%d")));
+                    if ui.add_enabled(self.end_date < today, egui::Button::new("►")).clicked() {
+                        self.end_date = match period {
+                            ReportPeriod::Week => self.end_date + Duration::days(7),
+                            ReportPeriod::Month => last_day_of_next_month(self.end_date),
+                        }.min(today);
+                    }
+                });
+                Ok((start, self.end_date))
+            }
+            None => {
+                ui.horizontal(|ui| {
+                    ui.label("From:");
+                    ui.add(egui::TextEdit::singleline(&mut self.custom_start).desired_width(90.0));
+                    ui.label("To:");
+                    ui.add(egui::TextEdit::singleline(&mut self.custom_end).desired_width(90.0));
+                });
+                validate_date(&self.custom_start).and_then(|start| {
+                    let end = validate_date(&self.custom_end)?;
+                    if start > end {
+                        Err("The start date must not be after the end date.".to_string())
+                    } else {
+                        Ok((start, end))
+                    }
+                })
+            }
+        };
+
+        ui.separator();
+        match range {
+            Ok((start, end)) => show_report(ui, self.refresh_report(db, start, end)),
+            Err(e) => {
+                ui.label(egui::RichText::new(e).color(egui::Color32::RED));
+            }
+        }
+
+        ui.separator();
+        if ui.button("Back").clicked() {
+            *current_state = AppState::Home;
+        }
+    }
+
+    /// Computes the report again only when the range, the user or the data changed.
+    fn refresh_report(&mut self, db: &Database, start: NaiveDate, end: NaiveDate) -> &RangeReport {
+        let key = ReportKey { start, end, user_id: db.current_user.clone(), data_revision: db.data_revision() };
+        if self.report.as_ref().map(|(cached, _)| cached) != Some(&key) {
+            self.report = Some((key, range_report(db, &db.current_user, start, end)));
+        }
+        &self.report.as_ref().unwrap().1
+    }
+}
+
+fn show_report(ui: &mut egui::Ui, report: &RangeReport) {
+    if report.logged_days == 0 && report.hydrated_days == 0 {
+        ui.label("No entries in this range.");
+        return;
+    }
+
+    ui.label(format!("Days logged: {} of {}", report.logged_days, report.days.len()));
+    ui.label(format!("Total: {:.0} kcal (target {:.0})", report.total_consumed, report.total_target));
+    ui.label(format!("Daily average: {:.0} kcal (target {:.0})", report.daily_average(), report.average_target()));
+    ui.label(format!("Days over target: {}   Days on or under target: {}", report.days_over, report.days_under));
+    let describe = |day: &DayTotal| {
+        format!("{} - {:.0} of {:.0} kcal", day.date.format("%a %Y-%m-%d"), day.consumed, day.target)
+    };
+    if let Some(day) = &report.best_day {
+        ui.label(format!("Best day: {}", describe(day)));
+    }
+    if let Some(day) = &report.worst_day {
+        ui.label(format!("Worst day: {}", describe(day)));
+    }
+    match report.macros.energy_split() {
+        Some((protein, carbs, fat)) => {
+            ui.label(format!(
+                "Macro split: protein {:.0}%, carbs {:.0}%, fat {:.0}% ({:.0} g / {:.0} g / {:.0} g)",
+                protein, carbs, fat, report.macros.protein_g, report.macros.carbs_g, report.macros.fat_g
+            ));
+        }
+        None => {
+            ui.label("Macro split: no foods with macros logged.");
+        }
+    }
+    if report.fasting_days > 0 {
+        ui.label(format!(
+            "Fasting: kept the eating window on {} of {} days; longest streak {} day(s)",
+            report.fasting_days_kept, report.fasting_days, report.longest_fasting_streak
+        ));
+    }
+    if report.hydrated_days > 0 {
+        ui.label(format!(
+            "Hydration: {:.0} ml a day on average; water goal met on {} of {} days",
+            report.average_fluid_ml(), report.days_water_goal_met, report.hydrated_days
+        ));
+    }
+
+    ui.separator();
+    egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+        egui::Grid::new("report_days").striped(true).show(ui, |ui| {
+            ui.label(egui::RichText::new("Date").strong());
+            ui.label(egui::RichText::new("Consumed").strong());
+            ui.label(egui::RichText::new("Target").strong());
+            ui.label(egui::RichText::new("Fluid (ml)").strong());
+            ui.end_row();
+            for day in report.days.iter().rev() {
+                ui.label(day.date.format("%a %Y-%m-%d").to_string());
+                if !day.logged {
+                    ui.label("-");
+                } else if day.consumed > day.target {
+                    ui.label(egui::RichText::new(format!("{:.0}", day.consumed)).color(egui::Color32::RED));
+                } else {
+                    ui.label(format!("{:.0}", day.consumed));
+                }
+                ui.label(format!("{:.0}", day.target));
+                if day.fluid_ml == 0.0 {
+                    ui.label("-");
+                } else if day.fluid_ml < day.water_goal_ml {
+                    ui.label(egui::RichText::new(format!("{:.0}", day.fluid_ml)).color(egui::Color32::YELLOW));
+                } else {
+                    ui.label(format!("{:.0}", day.fluid_ml));
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
+
+fn last_day_of_next_month(date: NaiveDate) -> NaiveDate {
+    let (year, month) = if date.month() >= 11 { (date.year() + 1, date.month() - 10) } else { (date.year(), date.month() + 2) };
+    NaiveDate::from_ymd_opt(year, month, 1).map_or(date, |first| first - Duration::days(1))
+}
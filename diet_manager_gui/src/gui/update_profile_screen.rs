@@ -1,13 +1,16 @@
 use eframe::egui;
-use crate::models::{Database, Gender, ActivityLevel, CalorieCalculationMethod};
+use diet_core::models::{Database, Gender, ActivityLevel, CalorieCalculationMethod, FastingSchedule, FoodTag, RestrictionPolicy, UserProfile};
 use crate::app_state::AppState;
-use uuid::Uuid;
+use crate::gui::allergens_editor;
 
-pub struct UpdateProfileScreen;
+pub struct UpdateProfileScreen {
+    draft: Option<UserProfile>, // Edited copy, written back on Save
+    save_result: Option<Result<String, String>>, // Outcome of the last Save
+}
 
 impl UpdateProfileScreen {
     pub fn new() -> Self {
-        Self
+        Self { draft: None, save_result: None }
     }
 
     pub fn render(
@@ -18,72 +21,140 @@ impl UpdateProfileScreen {
     ) {
         ui.heading("Update Profile");
 
-        if let Some(user) = db.users.get_mut(&db.current_user) {
+        if self.draft.is_none() {
+            self.draft = db.user_by_id(&db.current_user).map(|user| user.profile.clone());
+        }
+        if let Some(profile) = &mut self.draft {
             // Gender selection
             ui.label("Gender:");
             ui.horizontal(|ui| {
                 if ui.button("Male").clicked() {
-                    user.profile.gender = Gender::Male;
+                    profile.gender = Gender::Male;
                 }
                 if ui.button("Female").clicked() {
-                    user.profile.gender = Gender::Female;
+                    profile.gender = Gender::Female;
                 }
             });
 
             // Height input
             ui.label("Height (cm):");
-            ui.add(egui::Slider::new(&mut user.profile.height_cm, 100.0..=250.0));
+            ui.add(egui::Slider::new(&mut profile.height_cm, 100.0..=250.0));
 
             // Age input
             ui.label("Age:");
-            ui.add(egui::Slider::new(&mut user.profile.age, 1..=120));
+            ui.add(egui::Slider::new(&mut profile.age, 1..=120));
 
-            // Weight input
+            // Weight input, unless it follows the weight log
             ui.label("Weight (kg):");
-            ui.add(egui::Slider::new(&mut user.profile.weight_kg, 30.0..=200.0));
+            ui.add_enabled(!profile.weight_from_log, egui::Slider::new(&mut profile.weight_kg, 30.0..=200.0));
+            ui.checkbox(&mut profile.weight_from_log, "Use my latest logged weight (Weight & Measurements)");
 
             // Activity level selection
             ui.label("Activity Level:");
             ui.horizontal(|ui| {
                 if ui.button("Sedentary").clicked() {
-                    user.profile.activity_level = ActivityLevel::Sedentary;
+                    profile.activity_level = ActivityLevel::Sedentary;
                 }
                 if ui.button("Light").clicked() {
-                    user.profile.activity_level = ActivityLevel::Light;
+                    profile.activity_level = ActivityLevel::Light;
                 }
                 if ui.button("Moderate").clicked() {
-                    user.profile.activity_level = ActivityLevel::Moderate;
+                    profile.activity_level = ActivityLevel::Moderate;
                 }
                 if ui.button("Very Active").clicked() {
-                    user.profile.activity_level = ActivityLevel::VeryActive;
+                    profile.activity_level = ActivityLevel::VeryActive;
                 }
                 if ui.button("Extra Active").clicked() {
-                    user.profile.activity_level = ActivityLevel::ExtraActive;
+                    profile.activity_level = ActivityLevel::ExtraActive;
                 }
             });
 
+            ui.checkbox(&mut profile.eat_back_exercise, "Add calories burned by exercise to my daily goal");
+
             // Calorie calculation method selection
             ui.label("Calorie Calculation Method:");
             ui.horizontal(|ui| {
                 if ui.button("Harris-Benedict").clicked() {
-                    user.profile.calorie_method = CalorieCalculationMethod::HarrisBenedict;
+                    profile.calorie_method = CalorieCalculationMethod::HarrisBenedict;
                 }
                 if ui.button("Mifflin-St Jeor").clicked() {
-                    user.profile.calorie_method = CalorieCalculationMethod::MifflinStJeor;
+                    profile.calorie_method = CalorieCalculationMethod::MifflinStJeor;
+                }
+            });
+
+            // Allergies and dietary restrictions
+            allergens_editor(ui, "Allergies:", &mut profile.allergies);
+            ui.label("Dietary Requirements:");
+            ui.horizontal_wrapped(|ui| {
+                for tag in FoodTag::ALL {
+                    let mut required = profile.dietary_requirements.contains(&tag);
+                    if ui.checkbox(&mut required, tag.label()).changed() {
+                        if required {
+                            profile.dietary_requirements.push(tag);
+                        } else {
+                            profile.dietary_requirements.retain(|t| *t != tag);
+                        }
+                    }
                 }
             });
+            ui.horizontal(|ui| {
+                ui.label("When a food conflicts:");
+                ui.radio_value(&mut profile.restriction_policy, RestrictionPolicy::Warn, "Warn");
+                ui.radio_value(&mut profile.restriction_policy, RestrictionPolicy::Block, "Block");
+            });
+
+            // Intermittent fasting schedule
+            let mut fasting = profile.fasting.is_some();
+            if ui.checkbox(&mut fasting, "Intermittent fasting").changed() {
+                profile.fasting = fasting.then(FastingSchedule::default);
+            }
+            if let Some(schedule) = &mut profile.fasting {
+                ui.horizontal(|ui| {
+                    for hours in FastingSchedule::PRESETS {
+                        ui.radio_value(&mut schedule.window_hours, hours, format!("{}:{}", 24 - hours, hours));
+                    }
+                    ui.label("Eating window (hours):");
+                    ui.add(egui::DragValue::new(&mut schedule.window_hours).clamp_range(1..=23));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Window opens at:");
+                    let (mut hour, mut minute) = schedule.window_start.split_once(':')
+                        .and_then(|(h, m)| Some((h.parse::<u32>().ok()?, m.parse::<u32>().ok()?)))
+                        .unwrap_or((12, 0));
+                    ui.add(egui::DragValue::new(&mut hour).clamp_range(0..=23));
+                    ui.label(":");
+                    ui.add(egui::DragValue::new(&mut minute).clamp_range(0..=59));
+                    schedule.window_start = format!("{:02}:{:02}", hour, minute);
+                });
+                ui.label(schedule.label());
+            }
         }
 
         // Back button
         if ui.button("Back").clicked() {
+            self.draft = None;
+            self.save_result = None;
             *current_state = AppState::Home;
         }
 
         // Save button
         if ui.button("Save").clicked() {
-            // Save changes to the database
-            // This might involve writing back to a persistent storage if needed
-            println!("Profile updated for user: {}", db.current_user);
+            if let Some(profile) = self.draft.clone() {
+                let current_user = db.current_user.clone();
+                self.save_result = Some(db.update_profile(&current_user, profile).map(|()| "Profile saved.".to_string()));
+                if matches!(self.save_result, Some(Ok(_))) {
+                    self.draft = None;
+                }
+            }
+        }
+        match &self.save_result {
+            Some(Ok(message)) => {
+                ui.label(egui::RichText::new(message).color(egui::Color32::GREEN));
+            }
+            Some(Err(error)) => {
+                ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+            }
+            None => {}
         }
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,6 @@
 use eframe::egui;
 use chrono::NaiveDate;
-use crate::models::{Database, FoodLogEntry};
+use diet_core::models::{Database, FoodLogEntry, WaterEntry};
 use crate::app_state::AppState;
 
 pub struct ViewDailyLogScreen {
@@ -34,19 +34,22 @@ impl ViewDailyLogScreen {
 
         // Calculate nutrition data for the selected date
         let selected_date_str = self.selected_date.format("%Y-%m-%d").to_string();
-        let (total_calories, calories_goal, calories_remaining) = self.calculate_daily_nutrition(db, &db.current_user, &selected_date_str);
+        let summary = db.calculate_calories(&db.current_user, &selected_date_str);
 
         // Display nutrition summary
         ui.separator();
         ui.heading("Nutrition Summary");
-        ui.label(format!("Calories Consumed: {:.1}", total_calories));
-        ui.label(format!("Daily Goal: {:.1}", calories_goal));
+        ui.label(format!("Calories Consumed: {:.1}", summary.consumed));
+        if summary.burned > 0.0 {
+            ui.label(format!("Burned by Exercise: {:.1}", summary.burned));
+        }
+        ui.label(format!("Daily Goal: {:.1}", summary.target));
         
-        if calories_remaining >= 0.0 {
-            ui.label(egui::RichText::new(format!("Remaining: {:.1}", calories_remaining))
+        if summary.remaining >= 0.0 {
+            ui.label(egui::RichText::new(format!("Remaining: {:.1}", summary.remaining))
                 .color(egui::Color32::GREEN));
         } else {
-            ui.label(egui::RichText::new(format!("Over by: {:.1}", calories_remaining.abs()))
+            ui.label(egui::RichText::new(format!("Over by: {:.1}", summary.remaining.abs()))
                 .color(egui::Color32::RED));
         }
 
@@ -55,80 +58,102 @@ impl ViewDailyLogScreen {
         ui.heading("Food Entries");
         
         // Collect entries first to avoid borrowing issues
-        let entries: Vec<FoodLogEntry> = db.food_logs
-            .get(&db.current_user)
-            .map_or(Vec::new(), |entries| {
-                entries.iter()
-                    .filter(|entry| entry.date == selected_date_str)
-                    .cloned()
-                    .collect()
-            });
+        let entries: Vec<FoodLogEntry> = db.log_entries_for(&db.current_user, &selected_date_str)
+            .into_iter()
+            .cloned()
+            .collect();
 
         if entries.is_empty() {
             ui.label("No entries for this date.");
         } else {
-            for (i, entry) in entries.iter().enumerate() {
-                let food_name = db.basic_foods.get(&entry.food_id)
-                    .map(|f| f.name.clone())
-                    .or_else(|| db.composite_foods.get(&entry.food_id).map(|f| f.name.clone()))
-                    .unwrap_or_else(|| entry.food_id.clone());
+            // Group by meal, with a subtotal against each meal's budget
+            let breakdown = db.meal_breakdown(&db.current_user, &selected_date_str);
+            for (meal, consumed, budget) in breakdown {
+                let mut meal_entries: Vec<&FoodLogEntry> = entries.iter().filter(|e| e.meal == meal).collect();
+                if meal_entries.is_empty() {
+                    continue;
+                }
+                meal_entries.sort_by(|a, b| a.time.cmp(&b.time));
+
+                let subtotal = format!("{} - {:.1} / {:.1} kcal", meal.label(), consumed, budget);
+                if consumed > budget {
+                    ui.label(egui::RichText::new(subtotal).strong().color(egui::Color32::RED));
+                } else {
+                    ui.label(egui::RichText::new(subtotal).strong());
+                }
+
+                for entry in meal_entries {
+                    let food_name = db.entry_display_name(entry);
+                    let calories = db.entry_calories(entry);
+                    let time = entry.time.as_ref().map_or(String::new(), |t| format!("{} ", t));
+
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}{} ({} servings) - {:.1} kcal",
+                            time,
+                            food_name,
+                            entry.servings,
+                            calories
+                        ));
 
-                let calories = db.get_food_calories(&entry.food_id).unwrap_or(0.0) * entry.servings;
-                
-                ui.horizontal(|ui| {
-                    ui.label(format!("{}. {} ({} servings) - {:.1} kcal", 
-                        i + 1, 
-                        food_name,
-                        entry.servings,
-                        calories
-                    ));
-                    
-                    if ui.button("❌").clicked() {
-                        if let Some(entries) = db.food_logs.get_mut(&db.current_user) {
-                            if let Some(pos) = entries.iter().position(|e| 
-                                e.date == entry.date && 
-                                e.food_id == entry.food_id && 
-                                e.servings == entry.servings
-                            ) {
-                                entries.remove(pos);
-                            }
+                        if ui.button("❌").clicked() {
+                            let current_user = db.current_user.clone();
+                            db.delete_log_entry(&current_user, &entry.id);
                         }
+                    });
+
+                    let mut annotations = Vec::new();
+                    if let Some(note) = &entry.note {
+                        annotations.push(format!("📝 {}", note));
+                    }
+                    if let Some(hunger) = entry.hunger {
+                        annotations.push(format!("Hunger {}/5", hunger));
+                    }
+                    if let Some(mood) = entry.mood {
+                        annotations.push(format!("Felt {}/5", mood));
+                    }
+                    if let Some(photo) = &entry.photo_path {
+                        annotations.push(format!("📷 {}", photo));
+                    }
+                    if db.outside_eating_window(entry) {
+                        annotations.push("⏰ Outside eating window".to_string());
+                    }
+                    if !annotations.is_empty() {
+                        ui.indent(&entry.id, |ui| {
+                            ui.label(egui::RichText::new(annotations.join(" · ")).weak());
+                        });
                     }
-                });
+                }
             }
         }
 
+        // Water, plus the fluid of any beverages above
+        ui.separator();
+        let fluid = db.fluid_for(&db.current_user, &selected_date_str);
+        let goal = db.user_by_id(&db.current_user).map_or(0.0, |user| user.profile.water_goal_ml());
+        ui.heading(format!("Hydration - {:.0} / {:.0} ml", fluid, goal));
+        let water: Vec<WaterEntry> = db.water_entries_for(&db.current_user, &selected_date_str)
+            .into_iter()
+            .cloned()
+            .collect();
+        let from_beverages = fluid - water.iter().fold(0.0, |total, entry| total + entry.amount_ml);
+        if from_beverages > 0.0 {
+            ui.label(format!("Beverages: {:.0} ml", from_beverages));
+        }
+        for entry in &water {
+            ui.horizontal(|ui| {
+                let time = entry.time.as_ref().map_or(String::new(), |t| format!("{} ", t));
+                ui.label(format!("{}Water - {:.0} ml", time, entry.amount_ml));
+                if ui.button("❌").clicked() {
+                    let current_user = db.current_user.clone();
+                    db.delete_water_entry(&current_user, &entry.id);
+                }
+            });
+        }
+
         // Navigation button
         ui.separator();
         if ui.button("Back to Home").clicked() {
             *current_state = AppState::Home;
         }
     }
-
-    fn calculate_daily_nutrition(
-        &self,
-        db: &Database,
-        user_id: &str,
-        date: &str
-    ) -> (f32, f32, f32) {
-        // Calculate total calories consumed
-        let total_calories = db.food_logs
-            .get(user_id)
-            .map_or(0.0, |entries| {
-                entries.iter()
-                    .filter(|entry| entry.date == date)
-                    .map(|entry| db.get_food_calories(&entry.food_id).unwrap_or(0.0) * entry.servings)
-                    .sum()
-            });
-
-        // Get daily calorie goal from user profile
-        let calories_goal = db.users.values()
-            .find(|u| u.user_id == user_id)
-            .map(|user| user.profile.calculate_target_calories())
-            .unwrap_or(0.0);
-
-        let calories_remaining = calories_goal - total_calories;
-
-        (total_calories, calories_goal, calories_remaining)
-    }
 }
\ No newline at end of file
@@ -1,30 +1,38 @@
+use std::time::{Duration, SystemTime};
 use eframe::egui;
-use crate::models::Database;
-use crate::database::{load_database, save_database};
+use diet_core::models::Database;
+use diet_core::storage::{load_database, load_undo_histories, modified_time, save_database, save_undo_histories, DB_FILE};
 use crate::gui::{
     LoginScreen, RegisterScreen, HomeScreen, AddBasicFoodScreen, AddCompositeFoodScreen,
     ViewDailyLogScreen, AddFoodToLogScreen, EditFoodLogScreen, UpdateProfileScreen,
+    ManageFoodsScreen, AnnotationReportScreen, ReportsScreen, ChartsScreen, BodyLogScreen, ExerciseLogScreen,
 };
 use crate::app_state::AppState;
-use crate::gui::undo_manager::UndoManager;
+use diet_core::undo::UndoManager;
 
-mod models;
-mod database;
 mod app_state;
 mod gui;
 
 struct DietManagerApp {
     db: Database,
+    loaded: Option<SystemTime>, // Modification time of the database file as last loaded or saved
+    save_error: Option<String>,
     current_state: AppState,
     login_screen: LoginScreen,
     register_screen: RegisterScreen,
     home_screen: HomeScreen,
     add_basic_food_screen: AddBasicFoodScreen,
     add_composite_food_screen: AddCompositeFoodScreen,
+    manage_foods_screen: ManageFoodsScreen,
     view_daily_log_screen: ViewDailyLogScreen,
     add_food_to_log_screen: AddFoodToLogScreen,
     edit_food_log_screen: EditFoodLogScreen,
     update_profile_screen: UpdateProfileScreen,
+    annotation_report_screen: AnnotationReportScreen,
+    reports_screen: ReportsScreen,
+    charts_screen: ChartsScreen,
+    body_log_screen: BodyLogScreen,
+    exercise_log_screen: ExerciseLogScreen,
     undo_manager: UndoManager,
 }
 
@@ -32,48 +40,129 @@ impl Default for DietManagerApp {
     fn default() -> Self {
         Self {
             db: load_database(),
+            loaded: modified_time(DB_FILE),
+            save_error: None,
             current_state: AppState::Login,
             login_screen: LoginScreen::new(),
             register_screen: RegisterScreen::new(),
             home_screen: HomeScreen,
             add_basic_food_screen: AddBasicFoodScreen::new(),
             add_composite_food_screen: AddCompositeFoodScreen::new(),
+            manage_foods_screen: ManageFoodsScreen::new(),
             view_daily_log_screen: ViewDailyLogScreen::new(),
             add_food_to_log_screen: AddFoodToLogScreen::new(),
             edit_food_log_screen: EditFoodLogScreen::new(),
             update_profile_screen: UpdateProfileScreen::new(),
-            undo_manager: UndoManager::new(100),
+            annotation_report_screen: AnnotationReportScreen::new(),
+            reports_screen: ReportsScreen::new(),
+            charts_screen: ChartsScreen::new(),
+            body_log_screen: BodyLogScreen::new(),
+            exercise_log_screen: ExerciseLogScreen::new(),
+            undo_manager: UndoManager::with_histories(100, load_undo_histories(DB_FILE)),
+        }
+    }
+}
+
+impl DietManagerApp {
+    /// Reloads the database when another program saved it since, keeping who is logged in here.
+    fn reload_if_changed(&mut self) {
+        let modified = modified_time(DB_FILE);
+        if modified != self.loaded {
+            let current_user = std::mem::take(&mut self.db.current_user);
+            self.db = load_database();
+            self.db.current_user = current_user;
+            self.undo_manager.reload_histories(load_undo_histories(DB_FILE));
+            self.loaded = modified;
+        }
+    }
+
+    fn save(&mut self) {
+        self.save_error = save_database(&self.db).err().map(|e| format!("Failed to save database: {}", e));
+        self.loaded = modified_time(DB_FILE);
+        if let Err(e) = save_undo_histories(DB_FILE, self.undo_manager.take_unsaved_histories()) {
+            self.save_error = Some(format!("Failed to save undo history: {}", e));
         }
     }
 }
 
 impl eframe::App for DietManagerApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.reload_if_changed();
+        // Keep polling the file while idle so changes made elsewhere show up
+        ctx.request_repaint_after(Duration::from_secs(1));
+        let mut changed = false;
+
+        // Ctrl+Z / Ctrl+Shift+Z, unless a text field is using the keyboard for its own undo
+        let logged_in = !matches!(self.current_state, AppState::Login | AppState::Register);
+        if logged_in && !ctx.wants_keyboard_input() {
+            let (undo, redo) = ctx.input(|i| {
+                let z = i.modifiers.command && i.key_pressed(egui::Key::Z);
+                (z && !i.modifiers.shift, z && i.modifiers.shift)
+            });
+            let current_user = self.db.current_user.clone();
+            if undo {
+                self.undo_manager.undo(&current_user, &mut self.db);
+                changed = true;
+            } else if redo {
+                self.undo_manager.redo(&current_user, &mut self.db);
+                changed = true;
+            }
+        }
+
+        let previous_state = self.current_state;
         egui::CentralPanel::default().show(ctx, |ui| {
+            if let Some(error) = &self.save_error {
+                ui.label(egui::RichText::new(error).color(egui::Color32::RED));
+            }
             match self.current_state {
                 AppState::Login => self.login_screen.render(ui, &mut self.db, &mut self.current_state),
                 AppState::Register => self.register_screen.render(ui, &mut self.db, &mut self.current_state),
                 AppState::Home => self.home_screen.render(ui, &mut self.db, &mut self.current_state, &mut self.undo_manager),
                 AppState::AddBasicFood => self.add_basic_food_screen.render(ui, &mut self.db, &mut self.current_state),
                 AppState::AddCompositeFood => self.add_composite_food_screen.render(ui, &mut self.db, &mut self.current_state),
+                AppState::ManageFoods => self.manage_foods_screen.render(ui, &mut self.db, &mut self.current_state),
                 AppState::ViewDailyLog => self.view_daily_log_screen.render(ui, &mut self.db, &mut self.current_state),
                 AppState::AddFoodToLog => self.add_food_to_log_screen.render(ui, &mut self.db, &mut self.current_state),
                 AppState::EditFoodLog => self.edit_food_log_screen.render(ui, &mut self.db, &mut self.current_state),
                 AppState::UpdateProfile => self.update_profile_screen.render(ui, &mut self.db, &mut self.current_state),
+                AppState::AnnotationReport => self.annotation_report_screen.render(ui, &mut self.db, &mut self.current_state),
+                AppState::Reports => self.reports_screen.render(ui, &mut self.db, &mut self.current_state),
+                AppState::Charts => self.charts_screen.render(ui, &mut self.db, &mut self.current_state),
+                AppState::BodyLog => self.body_log_screen.render(ui, &mut self.db, &mut self.current_state),
+                AppState::ExerciseLog => self.exercise_log_screen.render(ui, &mut self.db, &mut self.current_state),
             }
         });
+
+        if self.current_state == AppState::AddFoodToLog && previous_state != AppState::AddFoodToLog {
+            self.add_food_to_log_screen.start();
+        }
+
+        // Whatever the screens changed this frame becomes one undoable command of the user
+        if let Some(command) = self.db.take_command() {
+            if !self.db.current_user.is_empty() {
+                self.undo_manager.push(&self.db.current_user, command);
+            }
+            changed = true;
+        }
+        // Saved straight away so the CLI, the TUI and the server see it
+        if changed {
+            self.save();
+        }
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
         if let Err(e) = save_database(&self.db) {
             eprintln!("Failed to save database: {}", e);
         }
+        if let Err(e) = save_undo_histories(DB_FILE, self.undo_manager.take_unsaved_histories()) {
+            eprintln!("Failed to save undo history: {}", e);
+        }
     }
 }
 
 fn main() {
     let options = eframe::NativeOptions::default();
-    eframe::run_native(
+    let _ = eframe::run_native(
         "Diet Manager",
         options,
         Box::new(|_cc| Box::new(DietManagerApp::default())),
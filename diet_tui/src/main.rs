@@ -0,0 +1,159 @@
+use std::io;
+use std::time::{Duration, SystemTime};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Paragraph;
+use ratatui::{DefaultTerminal, Frame};
+use diet_core::models::Database;
+use diet_core::storage::{
+    load_database_from, load_undo_histories, modified_time, save_database_to, save_undo_histories, DB_FILE,
+};
+use diet_core::undo::UndoManager;
+use crate::screens::{AddFoodToLogScreen, DailyLogScreen, EditFoodLogScreen, HomeScreen, LoginScreen};
+
+mod screens;
+
+/// The terminal counterpart of the GUI's `AppState`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Screen {
+    Login,
+    Home,
+    DailyLog,
+    AddFoodToLog,
+    EditFoodLog,
+    Exit,
+}
+
+struct App {
+    db: Database,
+    db_path: String,
+    loaded: Option<SystemTime>, // Modification time of the database file as last loaded or saved
+    current_screen: Screen,
+    login_screen: LoginScreen,
+    home_screen: HomeScreen,
+    daily_log_screen: DailyLogScreen,
+    add_food_to_log_screen: AddFoodToLogScreen,
+    edit_food_log_screen: EditFoodLogScreen,
+    undo_manager: UndoManager,
+    status: Option<String>, // Result of the last action, shown in the footer
+}
+
+impl App {
+    fn new(db_path: String) -> Self {
+        let db = load_database_from(&db_path);
+        let loaded = modified_time(&db_path);
+        let undo_manager = UndoManager::with_histories(100, load_undo_histories(&db_path));
+        Self {
+            db,
+            db_path,
+            loaded,
+            current_screen: Screen::Login, // Logins are not saved, so every run starts logged out
+            login_screen: LoginScreen::new(),
+            home_screen: HomeScreen::new(),
+            daily_log_screen: DailyLogScreen::new(),
+            add_food_to_log_screen: AddFoodToLogScreen::new(),
+            edit_food_log_screen: EditFoodLogScreen::new(),
+            undo_manager,
+            status: None,
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [body, footer] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        match self.current_screen {
+            Screen::Login => self.login_screen.render(frame, body),
+            Screen::Home => self.home_screen.render(frame, body, &self.db, &self.undo_manager),
+            Screen::DailyLog => self.daily_log_screen.render(frame, body, &self.db),
+            Screen::AddFoodToLog => self.add_food_to_log_screen.render(frame, body, &self.db),
+            Screen::EditFoodLog => self.edit_food_log_screen.render(frame, body, &self.db),
+            Screen::Exit => {}
+        }
+        if let Some(status) = &self.status {
+            frame.render_widget(Paragraph::new(status.as_str()).style(Style::default().fg(Color::Yellow)), footer);
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.current_screen = Screen::Exit;
+            return;
+        }
+        let status = match self.current_screen {
+            Screen::Login => self.login_screen.handle_key(key, &mut self.db, &mut self.current_screen),
+            Screen::Home => self.home_screen.handle_key(key, &mut self.db, &mut self.current_screen, &mut self.undo_manager),
+            Screen::DailyLog => self.daily_log_screen.handle_key(key, &mut self.current_screen),
+            Screen::AddFoodToLog => self.add_food_to_log_screen.handle_key(key, &mut self.db, &mut self.current_screen),
+            Screen::EditFoodLog => self.edit_food_log_screen.handle_key(key, &mut self.db, &mut self.current_screen),
+            Screen::Exit => None,
+        };
+        // Each keypress that changed data is one undoable step, saved straight away like the CLI does.
+        // Undo and redo change data without a new command but always report a status.
+        let command = self.db.take_command();
+        let changed = command.is_some() || status.is_some();
+        if let Some(command) = command {
+            self.undo_manager.push(&self.db.current_user, command);
+        }
+        if status.is_some() {
+            self.status = status;
+        }
+        if changed {
+            self.save();
+        }
+    }
+
+    /// Reloads the database when another program saved it since, keeping who is logged in here.
+    fn reload_if_changed(&mut self) {
+        let modified = modified_time(&self.db_path);
+        if modified != self.loaded {
+            let current_user = std::mem::take(&mut self.db.current_user);
+            self.db = load_database_from(&self.db_path);
+            self.db.current_user = current_user;
+            self.undo_manager.reload_histories(load_undo_histories(&self.db_path));
+            self.loaded = modified;
+        }
+    }
+
+    fn save(&mut self) {
+        if let Err(e) = save_database_to(&self.db, &self.db_path) {
+            self.status = Some(format!("Failed to save database: {}", e));
+        }
+        self.loaded = modified_time(&self.db_path);
+        // Only the users whose history changed here, so other front ends keep theirs
+        if let Err(e) = save_undo_histories(&self.db_path, self.undo_manager.take_unsaved_histories()) {
+            self.status = Some(format!("Failed to save undo history: {}", e));
+        }
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    while app.current_screen != Screen::Exit {
+        app.reload_if_changed();
+        terminal.draw(|frame| app.render(frame))?;
+        // Wake up now and then to pick up changes made elsewhere
+        if !event::poll(Duration::from_secs(1))? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.reload_if_changed();
+                app.handle_key(key);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    // Usage: diet_tui [DATABASE_FILE]
+    let db_path = std::env::args().nth(1).unwrap_or_else(|| DB_FILE.to_string());
+    let mut app = App::new(db_path);
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+
+    save_database_to(&app.db, &app.db_path)?;
+    save_undo_histories(&app.db_path, app.undo_manager.take_unsaved_histories())?;
+    result
+}
@@ -0,0 +1,134 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+use diet_core::models::{Database, FoodLogEntry, MealType};
+use diet_core::search::search_foods;
+use diet_core::services::{log_entry, today};
+use crate::Screen;
+use super::{edit_text, move_selection};
+
+pub struct AddFoodToLogScreen {
+    keywords: String,
+    selected: usize,
+    servings: f32,
+    meal: MealType,
+    error_message: Option<String>,
+}
+
+impl AddFoodToLogScreen {
+    pub fn new() -> Self {
+        let now = chrono::Local::now().format("%H:%M").to_string();
+        Self {
+            keywords: String::new(),
+            selected: 0,
+            servings: 1.0,
+            meal: MealType::for_time(&now),
+            error_message: None,
+        }
+    }
+
+    /// Foods the user can log that match every typed word, best match first, as (name, id).
+    /// Without any words, the recently logged ones.
+    fn matching_foods(&self, db: &Database) -> Vec<(String, String)> {
+        search_foods(db, &db.current_user, &self.keywords, true).into_iter()
+            .map(|id| (db.food_display_name(&id), id))
+            .collect()
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, db: &Database) {
+        let [title, filter, foods, options, help] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ]).areas(area);
+
+        frame.render_widget(
+            Paragraph::new(format!("Add Food to Log - {}", today())).style(Style::default().add_modifier(Modifier::BOLD)),
+            title,
+        );
+        frame.render_widget(
+            Paragraph::new(self.keywords.as_str()).block(Block::default().borders(Borders::ALL).title(
+                if self.keywords.trim().is_empty() { "Search (showing recent foods)" } else { "Search" },
+            )),
+            filter,
+        );
+
+        // Foods that conflict with the user's restrictions are flagged, as in the GUI
+        let items: Vec<ListItem> = self.matching_foods(db).into_iter()
+            .map(|(name, id)| {
+                let calories = db.get_food_calories(&id).unwrap_or(0.0);
+                if db.restriction_conflicts(&db.current_user, &id).is_empty() {
+                    ListItem::new(format!("{} - {:.1} kcal", name, calories))
+                } else {
+                    ListItem::new(format!("{} - {:.1} kcal ⚠", name, calories)).style(Style::default().fg(Color::Yellow))
+                }
+            })
+            .collect();
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::default().borders(Borders::ALL).title("Select Food"))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            foods,
+            &mut state,
+        );
+
+        frame.render_widget(
+            Paragraph::new(format!("Servings: {:.2}   Meal: {}", self.servings, self.meal.label()))
+                .block(Block::default().borders(Borders::ALL)),
+            options,
+        );
+        let help_text = match &self.error_message {
+            Some(error) => Paragraph::new(error.as_str()).style(Style::default().fg(Color::Red)),
+            None => Paragraph::new("Type to filter  ↑/↓: food  ←/→: servings  Tab: meal  Enter: add  Esc: back"),
+        };
+        frame.render_widget(help_text, help);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, db: &mut Database, current_screen: &mut Screen) -> Option<String> {
+        let foods = self.matching_foods(db);
+        match key.code {
+            KeyCode::Esc => {
+                *self = Self::new();
+                *current_screen = Screen::Home;
+            }
+            KeyCode::Up => move_selection(&mut self.selected, foods.len(), -1),
+            KeyCode::Down => move_selection(&mut self.selected, foods.len(), 1),
+            KeyCode::Left => self.servings = (self.servings - 0.25).max(0.25),
+            KeyCode::Right => self.servings += 0.25,
+            KeyCode::Tab => {
+                let next = MealType::ALL.iter().position(|meal| *meal == self.meal).map_or(0, |i| (i + 1) % MealType::ALL.len());
+                self.meal = MealType::ALL[next];
+            }
+            KeyCode::Enter => {
+                let Some((name, food_id)) = foods.get(self.selected) else {
+                    self.error_message = Some("Please select a food.".to_string());
+                    return None;
+                };
+                let mut entry = FoodLogEntry::new(&db.current_user, &today(), food_id, self.servings);
+                entry.meal = self.meal;
+                entry.time = Some(chrono::Local::now().format("%H:%M").to_string());
+                match log_entry(db, entry) {
+                    Ok(_) => {
+                        let status = format!("Logged {} serving(s) of {}.", self.servings, name);
+                        *self = Self::new();
+                        *current_screen = Screen::Home;
+                        return Some(status);
+                    }
+                    Err(e) => self.error_message = Some(e),
+                }
+            }
+            code => {
+                if edit_text(&mut self.keywords, code) {
+                    self.selected = 0;
+                    self.error_message = None;
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::NaiveDate;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+use diet_core::models::{Database, FoodLogEntry};
+use crate::Screen;
+
+pub struct DailyLogScreen {
+    selected_date: NaiveDate,
+}
+
+impl DailyLogScreen {
+    pub fn new() -> Self {
+        Self {
+            selected_date: chrono::Local::now().date_naive(),
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, db: &Database) {
+        let [title, summary, entries] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Min(1),
+        ]).areas(area);
+
+        let date = self.selected_date.format("%Y-%m-%d").to_string();
+        frame.render_widget(
+            Paragraph::new(format!("Daily Log - ◄ {} ►   (←/→: change date, Esc: back)", date))
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+            title,
+        );
+
+        let totals = db.calculate_calories(&db.current_user, &date);
+        let remaining = if totals.remaining >= 0.0 {
+            format!("Remaining: {:.1}", totals.remaining)
+        } else {
+            format!("Over by: {:.1}", totals.remaining.abs())
+        };
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Consumed: {:.1}   Burned: {:.1}   Goal: {:.1}   {}",
+                totals.consumed, totals.burned, totals.target, remaining
+            ))
+                .block(Block::default().borders(Borders::ALL).title("Nutrition Summary")),
+            summary,
+        );
+
+        // Entries grouped by meal, with each meal's subtotal against its budget
+        let day_entries = db.log_entries_for(&db.current_user, &date);
+        let mut lines = Vec::new();
+        for (meal, consumed, budget) in db.meal_breakdown(&db.current_user, &date) {
+            let mut meal_entries: Vec<&FoodLogEntry> = day_entries.iter().copied().filter(|e| e.meal == meal).collect();
+            if meal_entries.is_empty() {
+                continue;
+            }
+            meal_entries.sort_by(|a, b| a.time.cmp(&b.time));
+            let style = if consumed > budget { Style::default().fg(Color::Red) } else { Style::default() };
+            lines.push(Line::styled(
+                format!("{} - {:.1} / {:.1} kcal", meal.label(), consumed, budget),
+                style.add_modifier(Modifier::BOLD),
+            ));
+            for entry in meal_entries {
+                lines.push(Line::from(format!(
+                    "  {:<5} {} ({} servings) - {:.1} kcal",
+                    entry.time.as_deref().unwrap_or(""),
+                    db.entry_display_name(entry),
+                    entry.servings,
+                    db.entry_calories(entry)
+                )));
+            }
+        }
+        if lines.is_empty() {
+            lines.push(Line::from("No entries for this date."));
+        }
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Food Entries")),
+            entries,
+        );
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, current_screen: &mut Screen) -> Option<String> {
+        match key.code {
+            KeyCode::Left => self.selected_date -= chrono::Duration::days(1),
+            KeyCode::Right => {
+                let tomorrow = self.selected_date.succ_opt().unwrap_or(self.selected_date);
+                if tomorrow <= chrono::Local::now().date_naive() {
+                    self.selected_date = tomorrow;
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => *current_screen = Screen::Home,
+            _ => {}
+        }
+        None
+    }
+}
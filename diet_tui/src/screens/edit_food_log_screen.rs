@@ -0,0 +1,119 @@
+use chrono::NaiveDate;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+use diet_core::models::{Database, FoodLogEntry, MealType};
+use diet_core::services::update_entry;
+use crate::Screen;
+use super::move_selection;
+
+pub struct EditFoodLogScreen {
+    selected_date: NaiveDate,
+    selected: usize,
+    error_message: Option<String>,
+}
+
+impl EditFoodLogScreen {
+    pub fn new() -> Self {
+        Self {
+            selected_date: chrono::Local::now().date_naive(),
+            selected: 0,
+            error_message: None,
+        }
+    }
+
+    fn entries(&self, db: &Database) -> Vec<FoodLogEntry> {
+        let date = self.selected_date.format("%Y-%m-%d").to_string();
+        let mut entries: Vec<FoodLogEntry> = db.log_entries_for(&db.current_user, &date).into_iter().cloned().collect();
+        entries.sort_by(|a, b| (a.meal, &a.time).cmp(&(b.meal, &b.time)));
+        entries
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, db: &Database) {
+        let [title, list, help] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(3),
+            Constraint::Length(2),
+        ]).areas(area);
+
+        frame.render_widget(
+            Paragraph::new(format!("Edit Food Log - ◄ {} ►", self.selected_date.format("%Y-%m-%d")))
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+            title,
+        );
+
+        let items: Vec<ListItem> = self.entries(db).iter()
+            .map(|entry| ListItem::new(format!(
+                "{:<9} {:<5} {} - {} servings, {:.1} kcal",
+                entry.meal.label(),
+                entry.time.as_deref().unwrap_or(""),
+                db.entry_display_name(entry),
+                entry.servings,
+                db.entry_calories(entry)
+            )))
+            .collect();
+        let empty = items.is_empty();
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(if empty { "No entries for this date." } else { "Entries" }))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            list,
+            &mut state,
+        );
+
+        let help_text = match &self.error_message {
+            Some(error) => Paragraph::new(error.as_str()).style(Style::default().fg(Color::Red)),
+            None => Paragraph::new("←/→: date  ↑/↓: entry  +/-: servings  m: meal  d: delete  Esc: back"),
+        };
+        frame.render_widget(help_text, help);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, db: &mut Database, current_screen: &mut Screen) -> Option<String> {
+        let entries = self.entries(db);
+        let selected = entries.get(self.selected).cloned();
+        self.error_message = None;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => *current_screen = Screen::Home,
+            KeyCode::Left => {
+                self.selected_date -= chrono::Duration::days(1);
+                self.selected = 0;
+            }
+            KeyCode::Right => {
+                let tomorrow = self.selected_date.succ_opt().unwrap_or(self.selected_date);
+                if tomorrow <= chrono::Local::now().date_naive() {
+                    self.selected_date = tomorrow;
+                    self.selected = 0;
+                }
+            }
+            KeyCode::Up => move_selection(&mut self.selected, entries.len(), -1),
+            KeyCode::Down => move_selection(&mut self.selected, entries.len(), 1),
+            KeyCode::Char('+') | KeyCode::Char('=') | KeyCode::Char('-') | KeyCode::Char('m') => {
+                let mut entry = selected?;
+                match key.code {
+                    KeyCode::Char('-') => entry.servings = (entry.servings - 0.25).max(entry.servings.min(0.25)),
+                    KeyCode::Char('m') => {
+                        let next = MealType::ALL.iter().position(|meal| *meal == entry.meal).map_or(0, |i| (i + 1) % MealType::ALL.len());
+                        entry.meal = MealType::ALL[next];
+                    }
+                    _ => entry.servings += 0.25,
+                }
+                if let Err(e) = update_entry(db, entry) {
+                    self.error_message = Some(e);
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(entry) = selected {
+                    let current_user = db.current_user.clone();
+                    db.delete_log_entry(&current_user, &entry.id);
+                    move_selection(&mut self.selected, entries.len() - 1, 0);
+                    return Some(format!("Deleted {}.", db.entry_display_name(&entry)));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
@@ -0,0 +1,132 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+use diet_core::models::Database;
+use diet_core::services::today;
+use diet_core::undo::UndoManager;
+use crate::Screen;
+use super::move_selection;
+
+const MENU: [&str; 7] = ["View Daily Log", "Add Food to Log", "Edit Food Log", "Undo", "Redo", "Logout", "Quit"];
+
+pub struct HomeScreen {
+    selected: usize,
+}
+
+impl HomeScreen {
+    pub fn new() -> Self {
+        Self { selected: 0 }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, db: &Database, undo_manager: &UndoManager) {
+        let [title, summary, gauge, menu] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(7),
+            Constraint::Length(3),
+            Constraint::Min(MENU.len() as u16 + 2),
+        ]).areas(area);
+
+        let username = db.user_by_id(&db.current_user).map_or("", |user| user.username.as_str());
+        frame.render_widget(
+            Paragraph::new(format!("Welcome, {}!", username)).style(Style::default().add_modifier(Modifier::BOLD)),
+            title,
+        );
+
+        // Today's summary, per meal
+        let date = today();
+        let totals = db.calculate_calories(&db.current_user, &date);
+        let mut lines = vec![format!("Consumed {:.0} of {:.0} kcal", totals.consumed, totals.target)];
+        let remaining = if totals.remaining >= 0.0 {
+            format!("Remaining: {:.0}", totals.remaining)
+        } else {
+            format!("Over by: {:.0}", totals.remaining.abs())
+        };
+        lines.push(if totals.burned > 0.0 { format!("{}   Burned: {:.0}", remaining, totals.burned) } else { remaining });
+        for (meal, consumed, budget) in db.meal_breakdown(&db.current_user, &date) {
+            lines.push(format!("  {:<9} {:>6.0} / {:.0}", meal.label(), consumed, budget));
+        }
+        frame.render_widget(
+            Paragraph::new(lines.join("\n")).block(Block::default().borders(Borders::ALL).title(format!("Today ({})", date))),
+            summary,
+        );
+
+        let progress = if totals.target > 0.0 { totals.consumed / totals.target } else { 0.0 };
+        let color = if progress > 1.0 { Color::Red } else { Color::Green };
+        frame.render_widget(
+            Gauge::default()
+                .block(Block::default().borders(Borders::ALL))
+                .gauge_style(Style::default().fg(color))
+                .ratio(progress.clamp(0.0, 1.0) as f64)
+                .label(format!("{:.1}% of daily goal", progress * 100.0)),
+            gauge,
+        );
+
+        // Menu, with the actions undo and redo would apply
+        let items: Vec<ListItem> = MENU.iter()
+            .map(|item| {
+                let detail = match *item {
+                    "Undo" => undo_manager.undo_labels(&db.current_user).first().map(|l| format!(" ({})", l)),
+                    "Redo" => undo_manager.redo_labels(&db.current_user).first().map(|l| format!(" ({})", l)),
+                    _ => None,
+                };
+                ListItem::new(format!("{}{}", item, detail.unwrap_or_default()))
+            })
+            .collect();
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::default().borders(Borders::ALL).title("↑/↓ and Enter, u: undo, r: redo"))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            menu,
+            &mut state,
+        );
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: KeyEvent,
+        db: &mut Database,
+        current_screen: &mut Screen,
+        undo_manager: &mut UndoManager,
+    ) -> Option<String> {
+        let action = match key.code {
+            KeyCode::Up => {
+                move_selection(&mut self.selected, MENU.len(), -1);
+                return None;
+            }
+            KeyCode::Down => {
+                move_selection(&mut self.selected, MENU.len(), 1);
+                return None;
+            }
+            KeyCode::Enter => MENU[self.selected],
+            KeyCode::Char('u') => "Undo",
+            KeyCode::Char('r') => "Redo",
+            KeyCode::Char('q') | KeyCode::Esc => "Quit",
+            _ => return None,
+        };
+
+        let current_user = db.current_user.clone();
+        match action {
+            "View Daily Log" => *current_screen = Screen::DailyLog,
+            "Add Food to Log" => *current_screen = Screen::AddFoodToLog,
+            "Edit Food Log" => *current_screen = Screen::EditFoodLog,
+            "Undo" => {
+                undo_manager.undo(&current_user, db);
+                return undo_manager.status(&current_user).map(str::to_string);
+            }
+            "Redo" => {
+                undo_manager.redo(&current_user, db);
+                return undo_manager.status(&current_user).map(str::to_string);
+            }
+            "Logout" => {
+                db.current_user.clear();
+                *current_screen = Screen::Login;
+                return Some("Logged out.".to_string());
+            }
+            _ => *current_screen = Screen::Exit,
+        }
+        None
+    }
+}
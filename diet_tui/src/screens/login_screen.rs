@@ -0,0 +1,70 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Frame;
+use diet_core::models::Database;
+use diet_core::services::login;
+use crate::Screen;
+use super::edit_text;
+
+pub struct LoginScreen {
+    username: String,
+    password: String,
+    editing_password: bool,
+    error_message: Option<String>,
+}
+
+impl LoginScreen {
+    pub fn new() -> Self {
+        Self {
+            username: String::new(),
+            password: String::new(),
+            editing_password: false,
+            error_message: None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let [title, username, password, help, error_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(2),
+            Constraint::Min(1),
+        ]).areas(area);
+
+        frame.render_widget(Paragraph::new("Login").style(Style::default().add_modifier(Modifier::BOLD)), title);
+        let field = |label: &'static str, active: bool| {
+            let style = if active { Style::default().fg(Color::Cyan) } else { Style::default() };
+            Block::default().borders(Borders::ALL).title(label).border_style(style)
+        };
+        frame.render_widget(Paragraph::new(self.username.as_str()).block(field("Username", !self.editing_password)), username);
+        frame.render_widget(Paragraph::new("*".repeat(self.password.len())).block(field("Password", self.editing_password)), password);
+        frame.render_widget(Paragraph::new("Tab: switch field   Enter: log in   Esc: quit"), help);
+        if let Some(error) = &self.error_message {
+            frame.render_widget(Paragraph::new(error.as_str()).style(Style::default().fg(Color::Red)), error_area);
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, db: &mut Database, current_screen: &mut Screen) -> Option<String> {
+        match key.code {
+            KeyCode::Esc => *current_screen = Screen::Exit,
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => self.editing_password = !self.editing_password,
+            KeyCode::Enter => match login(db, &self.username, &self.password) {
+                Ok(_) => {
+                    let status = format!("Logged in as {}.", self.username);
+                    *self = Self::new();
+                    *current_screen = Screen::Home;
+                    return Some(status);
+                }
+                Err(e) => self.error_message = Some(e),
+            },
+            code => {
+                let field = if self.editing_password { &mut self.password } else { &mut self.username };
+                edit_text(field, code);
+            }
+        }
+        None
+    }
+}
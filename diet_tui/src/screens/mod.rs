@@ -0,0 +1,34 @@
+mod login_screen;
+mod home_screen;
+mod daily_log_screen;
+mod add_food_to_log_screen;
+mod edit_food_log_screen;
+
+pub use login_screen::*;
+pub use home_screen::*;
+pub use daily_log_screen::*;
+pub use add_food_to_log_screen::*;
+pub use edit_food_log_screen::*;
+
+use ratatui::crossterm::event::KeyCode;
+
+/// Applies a typing key to a text field. Returns true if the key was used.
+fn edit_text(text: &mut String, code: KeyCode) -> bool {
+    match code {
+        KeyCode::Char(c) => text.push(c),
+        KeyCode::Backspace => {
+            text.pop();
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Moves a list selection up or down by `delta`, staying within `len` items.
+fn move_selection(selected: &mut usize, len: usize, delta: isize) {
+    if len == 0 {
+        *selected = 0;
+    } else {
+        *selected = selected.saturating_add_signed(delta).min(len - 1);
+    }
+}
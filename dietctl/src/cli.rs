@@ -0,0 +1,183 @@
+use clap::{Parser, Subcommand};
+use diet_core::models::{ActivityLevel, CalorieCalculationMethod, Gender, MealType};
+use diet_core::storage::DB_FILE;
+
+/// Log food and inspect your diet from the terminal.
+#[derive(Parser, Debug)]
+#[command(name = "dietctl", version)]
+pub struct Cli {
+    /// Database file shared with the GUI
+    #[arg(long, global = true, default_value = DB_FILE)]
+    pub db: String,
+
+    /// Print machine-readable JSON instead of text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create an account and log in as it
+    Register {
+        username: String,
+        #[arg(long)]
+        password: String,
+        #[arg(long, value_parser = parse_gender)]
+        gender: Gender,
+        #[arg(long)]
+        height_cm: f32,
+        #[arg(long)]
+        age: u32,
+        #[arg(long)]
+        weight_kg: f32,
+        #[arg(long, value_parser = parse_activity_level, default_value = "sedentary")]
+        activity: ActivityLevel,
+        #[arg(long, value_parser = parse_calorie_method, default_value = "harris-benedict")]
+        method: CalorieCalculationMethod,
+    },
+    /// Log in; later commands act as this user
+    Login {
+        username: String,
+        #[arg(long)]
+        password: String,
+    },
+    Logout,
+    /// List the foods you can log
+    Foods {
+        /// Search words, matched against names, brands and keywords (typos are tolerated)
+        keywords: Vec<String>,
+        /// Match any word instead of all of them
+        #[arg(long)]
+        any: bool,
+    },
+    /// Add a basic food
+    AddFood {
+        name: String,
+        #[arg(long)]
+        calories: String,
+        /// Comma-separated keywords
+        #[arg(long, default_value = "")]
+        keywords: String,
+        #[arg(long)]
+        brand: Option<String>,
+        /// Keep the food to yourself instead of sharing it with the household
+        #[arg(long)]
+        private: bool,
+    },
+    /// Add a composite food made of other foods
+    AddComposite {
+        /// Identifier, unique among your foods
+        id: String,
+        #[arg(long)]
+        name: String,
+        /// A component as FOOD=SERVINGS, where FOOD is an id or name; repeat for each one
+        #[arg(long = "component", required = true, value_parser = parse_component)]
+        components: Vec<(String, f32)>,
+        /// Comma-separated keywords
+        #[arg(long, default_value = "")]
+        keywords: String,
+        /// How many portions the recipe makes
+        #[arg(long, default_value_t = 1.0)]
+        portions: f32,
+        /// Keep the food to yourself instead of sharing it with the household
+        #[arg(long)]
+        private: bool,
+    },
+    /// Log servings of a food
+    Log {
+        /// Food id or name
+        food: String,
+        #[arg(long, default_value_t = 1.0)]
+        servings: f32,
+        /// ISO 8601 date; defaults to today
+        #[arg(long)]
+        date: Option<String>,
+        #[arg(long, value_parser = parse_meal)]
+        meal: Option<MealType>,
+        /// Time of day as HH:MM
+        #[arg(long)]
+        time: Option<String>,
+    },
+    /// Log calories without a food
+    QuickAdd {
+        label: String,
+        calories: f32,
+        #[arg(long)]
+        date: Option<String>,
+        #[arg(long, value_parser = parse_meal)]
+        meal: Option<MealType>,
+        #[arg(long)]
+        time: Option<String>,
+    },
+    /// Show the entries of a day
+    List {
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// Change a log entry
+    Edit {
+        entry_id: String,
+        #[arg(long)]
+        servings: Option<f32>,
+        #[arg(long, value_parser = parse_meal)]
+        meal: Option<MealType>,
+        #[arg(long)]
+        time: Option<String>,
+    },
+    /// Delete a log entry
+    Delete {
+        entry_id: String,
+    },
+    /// Calories consumed against the daily target
+    Summary {
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// Serve the database as a JSON API on localhost until interrupted
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+    },
+}
+
+fn parse_gender(s: &str) -> Result<Gender, String> {
+    match s.to_lowercase().as_str() {
+        "male" | "m" => Ok(Gender::Male),
+        "female" | "f" => Ok(Gender::Female),
+        _ => Err("expected male or female".to_string()),
+    }
+}
+
+fn parse_activity_level(s: &str) -> Result<ActivityLevel, String> {
+    match s.to_lowercase().as_str() {
+        "sedentary" => Ok(ActivityLevel::Sedentary),
+        "light" => Ok(ActivityLevel::Light),
+        "moderate" => Ok(ActivityLevel::Moderate),
+        "very-active" => Ok(ActivityLevel::VeryActive),
+        "extra-active" => Ok(ActivityLevel::ExtraActive),
+        _ => Err("expected sedentary, light, moderate, very-active or extra-active".to_string()),
+    }
+}
+
+fn parse_calorie_method(s: &str) -> Result<CalorieCalculationMethod, String> {
+    match s.to_lowercase().as_str() {
+        "harris-benedict" => Ok(CalorieCalculationMethod::HarrisBenedict),
+        "mifflin-st-jeor" => Ok(CalorieCalculationMethod::MifflinStJeor),
+        _ => Err("expected harris-benedict or mifflin-st-jeor".to_string()),
+    }
+}
+
+fn parse_meal(s: &str) -> Result<MealType, String> {
+    MealType::ALL.into_iter()
+        .find(|meal| meal.label().eq_ignore_ascii_case(s))
+        .ok_or_else(|| "expected breakfast, lunch, dinner or snack".to_string())
+}
+
+fn parse_component(s: &str) -> Result<(String, f32), String> {
+    let (food, servings) = s.rsplit_once('=').ok_or("expected FOOD=SERVINGS")?;
+    let servings = servings.parse().map_err(|_| format!("'{}' is not a number of servings", servings))?;
+    Ok((food.to_string(), servings))
+}
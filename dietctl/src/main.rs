@@ -0,0 +1,356 @@
+use clap::Parser;
+use serde::Serialize;
+use serde_json::{json, Value};
+use diet_core::models::{
+    BasicFood, CompositeFood, Database, FoodComponent, FoodLogEntry, MealType, RecipeYield, Visibility,
+};
+use diet_core::search::{search_foods, terms};
+use diet_core::services::{self, check_composite_food, log_entry, resolve_food, split_keywords, update_entry, NewUser};
+use diet_core::storage::{load_database_from, load_session, save_database_to, save_session};
+use diet_core::validation::{parse_calories, validate_food_name, validate_time};
+use crate::cli::{Cli, Command};
+
+mod cli;
+mod server;
+
+/// What a command prints: a line of text, or a JSON value with `--json`.
+struct Output {
+    text: String,
+    json: Value,
+}
+
+impl Output {
+    fn new(text: impl Into<String>, json: Value) -> Self {
+        Self { text: text.into(), json }
+    }
+}
+
+/// A log entry with its food name and calories resolved.
+#[derive(Serialize)]
+struct EntryView<'a> {
+    #[serde(flatten)]
+    entry: &'a FoodLogEntry,
+    name: String,
+    calories: f32,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Command::Serve { port } = cli.command {
+        if let Err(e) = server::serve(&cli.db, port) {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let mut db = load_database_from(&cli.db);
+    db.current_user = load_session(&cli.db);
+    let changes_data = !matches!(
+        cli.command,
+        Command::Foods { .. } | Command::List { .. } | Command::Summary { .. }
+    );
+
+    let result = run(&mut db, cli.command).and_then(|output| {
+        if changes_data {
+            save_database_to(&db, &cli.db).map_err(|e| format!("Failed to save database: {}", e))?;
+            save_session(&cli.db, &db.current_user).map_err(|e| format!("Failed to save login: {}", e))?;
+        }
+        Ok(output)
+    });
+
+    match result {
+        Ok(output) if cli.json => println!("{}", serde_json::to_string_pretty(&output.json).unwrap()),
+        Ok(output) => println!("{}", output.text),
+        Err(e) => {
+            if cli.json {
+                println!("{}", json!({ "error": e }));
+            } else {
+                eprintln!("error: {}", e);
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(db: &mut Database, command: Command) -> Result<Output, String> {
+    match command {
+        Command::Register { username, password, gender, height_cm, age, weight_kg, activity, method } => {
+            let user_id = services::register(db, NewUser {
+                username: username.clone(),
+                password,
+                gender,
+                height_cm,
+                age,
+                weight_kg,
+                activity_level: activity,
+                calorie_method: method,
+            })?;
+            Ok(Output::new(format!("Registered and logged in as {}.", username), json!({ "user_id": user_id })))
+        }
+        Command::Login { username, password } => {
+            let user_id = services::login(db, &username, &password)?;
+            Ok(Output::new(format!("Logged in as {}.", username), json!({ "user_id": user_id })))
+        }
+        Command::Logout => {
+            db.current_user.clear();
+            Ok(Output::new("Logged out.", json!({})))
+        }
+        Command::Foods { keywords, any } => {
+            let user_id = current_user(db)?;
+            Ok(foods(db, &user_id, &keywords, any))
+        }
+        Command::AddFood { name, calories, keywords, brand, private } => {
+            let user_id = current_user(db)?;
+            let food = new_basic_food(&user_id, &name, &calories, split_keywords(&keywords), brand, private)?;
+            let id = food.id.clone();
+            let text = format!("Added '{}' ({}).", food.display_name(), id);
+            db.save_basic_food(food);
+            Ok(Output::new(text, json!({ "id": id })))
+        }
+        Command::AddComposite { id, name, components, keywords, portions, private } => {
+            let user_id = current_user(db)?;
+            let key = Database::food_key(&user_id, id.trim());
+            if db.food_exists(&key) {
+                return Err(format!("You already have a food with identifier '{}'.", id.trim()));
+            }
+            let components = components.into_iter()
+                .map(|(food, servings)| {
+                    Ok(FoodComponent { food_id: resolve_food(db, &user_id, &food)?, servings })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let food = check_composite_food(CompositeFood {
+                id: key.clone(),
+                name,
+                keywords: split_keywords(&keywords),
+                components,
+                version: 1,
+                recipe_yield: RecipeYield::Portions(portions),
+                raw_weight_g: None,
+                category: None,
+                tags: Vec::new(),
+                owner: user_id,
+                visibility: if private { Visibility::Private } else { Visibility::Shared },
+            })?;
+            let text = format!("Added composite food '{}' ({}).", food.name, key);
+            db.save_composite_food(food);
+            Ok(Output::new(text, json!({ "id": key })))
+        }
+        Command::Log { food, servings, date, meal, time } => {
+            let user_id = current_user(db)?;
+            let food_id = resolve_food(db, &user_id, &food)?;
+            let mut entry = FoodLogEntry::new(&user_id, &date.unwrap_or_else(services::today), &food_id, servings);
+            place_entry(&mut entry, meal, time)?;
+            let outside = db.outside_eating_window(&entry);
+            let id = log_entry(db, entry)?;
+            let mut text = format!("Logged {} serving(s) of {} ({}).", servings, db.food_display_name(&food_id), id);
+            if outside {
+                text.push_str(&eating_window_warning(db, &user_id));
+            }
+            Ok(Output::new(text, json!({ "id": id, "outside_eating_window": outside })))
+        }
+        Command::QuickAdd { label, calories, date, meal, time } => {
+            let user_id = current_user(db)?;
+            let mut entry = FoodLogEntry::quick(&user_id, &date.unwrap_or_else(services::today), &label, calories, None);
+            place_entry(&mut entry, meal, time)?;
+            let outside = db.outside_eating_window(&entry);
+            let id = log_entry(db, entry)?;
+            let mut text = format!("Logged {:.1} kcal of {} ({}).", calories, label, id);
+            if outside {
+                text.push_str(&eating_window_warning(db, &user_id));
+            }
+            Ok(Output::new(text, json!({ "id": id, "outside_eating_window": outside })))
+        }
+        Command::List { date } => {
+            let user_id = current_user(db)?;
+            Ok(list(db, &user_id, &date.unwrap_or_else(services::today)))
+        }
+        Command::Edit { entry_id, servings, meal, time } => {
+            let user_id = current_user(db)?;
+            edit_entry(db, &user_id, &entry_id, servings, meal, time)?;
+            Ok(Output::new(format!("Updated entry {}.", entry_id), json!({ "id": entry_id })))
+        }
+        Command::Delete { entry_id } => {
+            let user_id = current_user(db)?;
+            let entry = db.delete_log_entry(&user_id, &entry_id)
+                .ok_or_else(|| format!("Log entry '{}' does not exist.", entry_id))?;
+            Ok(Output::new(format!("Deleted {} from {}.", db.entry_display_name(&entry), entry.date), json!({ "id": entry_id })))
+        }
+        Command::Summary { date } => {
+            let user_id = current_user(db)?;
+            Ok(summary(db, &user_id, &date.unwrap_or_else(services::today)))
+        }
+        Command::Serve { .. } => unreachable!("serve runs before any other command"),
+    }
+}
+
+/// The foods the user can see that match the search words, best match first, or all of them
+/// by name when there are none.
+fn foods(db: &Database, user_id: &str, keywords: &[String], any: bool) -> Output {
+    let query = keywords.join(" ");
+    let ids = if terms(&query).is_empty() {
+        // Every food, where an empty search would only list the recent ones
+        let mut ids: Vec<(String, String)> = db.basic_foods.keys().chain(db.composite_foods.keys())
+            .filter(|id| db.food_visible_to(id, user_id))
+            .map(|id| (db.food_display_name(id), id.clone()))
+            .collect();
+        ids.sort();
+        ids.into_iter().map(|(_, id)| id).collect()
+    } else {
+        search_foods(db, user_id, &query, !any)
+    };
+    let foods: Vec<(String, String, f32)> = ids.into_iter()
+        .map(|id| (db.food_display_name(&id), id.clone(), db.get_food_calories(&id).unwrap_or(0.0)))
+        .collect();
+    let text = foods.iter()
+        .map(|(name, id, calories)| format!("{:<30} {:>8.1} kcal  {}", name, calories, id))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let json = foods.iter()
+        .map(|(name, id, calories)| json!({ "id": id, "name": name, "calories_per_serving": calories }))
+        .collect();
+    Output::new(text, Value::Array(json))
+}
+
+/// A new basic food owned by the user, after validating the name and calories.
+fn new_basic_food(
+    user_id: &str,
+    name: &str,
+    calories: &str,
+    keywords: Vec<String>,
+    brand: Option<String>,
+    private: bool,
+) -> Result<BasicFood, String> {
+    Ok(BasicFood {
+        id: BasicFood::new_id(),
+        name: validate_food_name(name)?,
+        keywords,
+        calories_per_serving: parse_calories(calories)?,
+        macros: None,
+        fluid_ml: None,
+        version: 1,
+        brand: brand.filter(|b| !b.trim().is_empty()),
+        description: None,
+        notes: String::new(),
+        category: None,
+        tags: Vec::new(),
+        allergens: Vec::new(),
+        owner: user_id.to_string(),
+        visibility: if private { Visibility::Private } else { Visibility::Shared },
+    })
+}
+
+/// Changes the given fields of one of the user's entries.
+fn edit_entry(
+    db: &mut Database,
+    user_id: &str,
+    entry_id: &str,
+    servings: Option<f32>,
+    meal: Option<MealType>,
+    time: Option<String>,
+) -> Result<(), String> {
+    let mut entry = db.find_log_entry(user_id, entry_id)
+        .cloned()
+        .ok_or_else(|| format!("Log entry '{}' does not exist.", entry_id))?;
+    if let Some(servings) = servings {
+        entry.servings = servings;
+    }
+    if let Some(meal) = meal {
+        entry.meal = meal;
+    }
+    if let Some(time) = time {
+        entry.time = validate_time(&time)?;
+    }
+    update_entry(db, entry)
+}
+
+/// The user's entries for a date, in meal and time order.
+fn list(db: &Database, user_id: &str, date: &str) -> Output {
+    let mut entries = db.log_entries_for(user_id, date);
+    entries.sort_by(|a, b| (a.meal, &a.time).cmp(&(b.meal, &b.time)));
+    let views: Vec<EntryView> = entries.into_iter()
+        .map(|entry| EntryView { entry, name: db.entry_display_name(entry), calories: db.entry_calories(entry) })
+        .collect();
+    let text = if views.is_empty() {
+        format!("No entries for {}.", date)
+    } else {
+        views.iter()
+            .map(|view| format!(
+                "{:<9} {:<5} {:<30} {:>5} x {:>8.1} kcal  {}",
+                view.entry.meal.label(),
+                view.entry.time.as_deref().unwrap_or(""),
+                view.name,
+                view.entry.servings,
+                view.calories,
+                view.entry.id
+            ))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    Output::new(text, serde_json::to_value(&views).unwrap())
+}
+
+/// Calories consumed against the target for a date, per meal.
+fn summary(db: &Database, user_id: &str, date: &str) -> Output {
+    let summary = db.calculate_calories(user_id, date);
+    let meals = db.meal_breakdown(user_id, date);
+    let mut text = format!(
+        "{}: {:.1} of {:.1} kcal, {}",
+        date,
+        summary.consumed,
+        summary.target,
+        if summary.remaining >= 0.0 {
+            format!("{:.1} remaining", summary.remaining)
+        } else {
+            format!("over by {:.1}", -summary.remaining)
+        }
+    );
+    if summary.burned > 0.0 {
+        text.push_str(&format!(" ({:.1} kcal burned by exercise)", summary.burned));
+    }
+    let fluid = db.fluid_for(user_id, date);
+    let water_goal = db.user_by_id(user_id).map_or(0.0, |user| user.profile.water_goal_ml());
+    text.push_str(&format!("\n  Fluid {:.0} of {:.0} ml", fluid, water_goal));
+    for (meal, consumed, budget) in &meals {
+        text.push_str(&format!("\n  {:<9} {:>8.1} / {:.1} kcal", meal.label(), consumed, budget));
+    }
+    let meals: Vec<Value> = meals.iter()
+        .map(|(meal, consumed, budget)| json!({ "meal": meal, "consumed": consumed, "budget": budget }))
+        .collect();
+    Output::new(text, json!({
+        "date": date,
+        "consumed": summary.consumed,
+        "base_target": summary.base_target,
+        "burned": summary.burned,
+        "target": summary.target,
+        "remaining": summary.remaining,
+        "fluid_ml": fluid,
+        "water_goal_ml": water_goal,
+        "meals": meals,
+    }))
+}
+
+fn current_user(db: &Database) -> Result<String, String> {
+    if db.current_user.is_empty() || db.user_by_id(&db.current_user).is_none() {
+        Err("Not logged in. Run `dietctl login <username> --password <password>` first.".to_string())
+    } else {
+        Ok(db.current_user.clone())
+    }
+}
+
+/// Sets the meal and time of a new entry. Without a meal, it is guessed from the time,
+/// which defaults to now when logging for today.
+fn place_entry(entry: &mut FoodLogEntry, meal: Option<MealType>, time: Option<String>) -> Result<(), String> {
+    entry.time = match time {
+        Some(time) => validate_time(&time)?,
+        None if entry.date == services::today() => Some(chrono::Local::now().format("%H:%M").to_string()),
+        None => None,
+    };
+    entry.meal = meal.unwrap_or_else(|| entry.time.as_deref().map(MealType::for_time).unwrap_or_default());
+    Ok(())
+}
+
+/// Added to the output of the log commands when an entry falls outside the eating window.
+fn eating_window_warning(db: &Database, user_id: &str) -> String {
+    let schedule = db.user_by_id(user_id).and_then(|user| user.profile.fasting.as_ref());
+    format!("\nWarning: this is outside your eating window ({}).", schedule.map_or(String::new(), |s| s.label()))
+}
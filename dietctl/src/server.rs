@@ -0,0 +1,308 @@
+//! `dietctl serve`: the database as a JSON API on localhost.
+//!
+//! Clients exchange a username and password for a token at `POST /login` and send it as
+//! `Authorization: Bearer <token>`. Writes are saved before responding.
+//!
+//! The GUI, the TUI, the CLI and this server all write the whole database file and none of
+//! them lock it. Each keeps what it loaded and reloads the file when its modification time
+//! changes, before handling the next request or input, so a write made elsewhere is picked up
+//! rather than overwritten. Only two writes landing in the same moment can still lose one of them.
+//!
+//! | Method | Path                    | Body / query                                   |
+//! |--------|-------------------------|------------------------------------------------|
+//! | POST   | /login                  | `{username, password}`                         |
+//! | POST   | /logout                 |                                                |
+//! | GET    | /users                  |                                                |
+//! | GET    | /users/me               |                                                |
+//! | GET    | /foods                  | `?keywords=a,b&any=true`                       |
+//! | POST   | /foods                  | `{name, calories, keywords, brand, private}`   |
+//! | GET    | /foods/{id}             |                                                |
+//! | GET    | /composite-foods        |                                                |
+//! | GET    | /composite-foods/{id}   |                                                |
+//! | GET    | /log                    | `?date=YYYY-MM-DD`                             |
+//! | POST   | /log                    | `{food, servings, date, meal, time}`, or `{label, calories, ...}` for a quick add |
+//! | PUT    | /log/{id}               | `{servings, meal, time}`                       |
+//! | DELETE | /log/{id}               |                                                |
+//! | GET    | /summary                | `?date=YYYY-MM-DD`                             |
+
+use std::collections::HashMap;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+use diet_core::models::{CompositeFood, Database, FoodLogEntry, MealType};
+use diet_core::services::{self, authenticate, log_entry, resolve_food, split_keywords};
+use diet_core::storage::{load_database_from, modified_time, save_database_to};
+use crate::{edit_entry, foods, list, new_basic_food, place_entry, summary};
+
+/// An error response: the HTTP status and a message for the `error` field.
+type ApiError = (u16, String);
+
+#[derive(Deserialize)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+struct NewFoodRequest {
+    name: String,
+    calories: f32,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    brand: Option<String>,
+    #[serde(default)]
+    private: bool,
+}
+
+#[derive(Deserialize)]
+struct LogRequest {
+    #[serde(default)]
+    food: Option<String>, // Food id or name
+    #[serde(default)]
+    label: Option<String>, // Quick add instead of a food
+    #[serde(default)]
+    calories: Option<f32>,
+    #[serde(default)]
+    servings: Option<f32>,
+    #[serde(default)]
+    date: Option<String>,
+    #[serde(default)]
+    meal: Option<MealType>,
+    #[serde(default)]
+    time: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EditRequest {
+    #[serde(default)]
+    servings: Option<f32>,
+    #[serde(default)]
+    meal: Option<MealType>,
+    #[serde(default)]
+    time: Option<String>,
+}
+
+/// Runs the server on 127.0.0.1 until the process is stopped.
+pub fn serve(db_path: &str, port: u16) -> Result<(), String> {
+    let server = Server::http(("127.0.0.1", port)).map_err(|e| format!("Cannot listen on port {}: {}", port, e))?;
+    println!("Serving {} on http://127.0.0.1:{}", db_path, port);
+
+    // Token -> user_id. Tokens last until logout or until the server stops.
+    let mut tokens: HashMap<String, String> = HashMap::new();
+    let mut db = load_database_from(db_path);
+    let mut loaded = modified_time(db_path);
+    for mut request in server.incoming_requests() {
+        // Pick up changes the other front ends saved since
+        let modified = modified_time(db_path);
+        if modified != loaded {
+            db = load_database_from(db_path);
+            loaded = modified;
+        }
+        let result = handle(&mut request, &mut db, &mut tokens).and_then(|(body, changed)| {
+            if changed {
+                // The journal is only used by the GUI's undo; nothing here undoes changes
+                db.take_command();
+                if let Err(e) = save_database_to(&db, db_path) {
+                    db = load_database_from(db_path);
+                    return Err((500, format!("Failed to save database: {}", e)));
+                }
+                loaded = modified_time(db_path);
+            }
+            Ok(body)
+        });
+        let (status, body) = match result {
+            Ok(body) => (200, body),
+            Err((status, error)) => (status, json!({ "error": error })),
+        };
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+        if let Err(e) = request.respond(response) {
+            eprintln!("Failed to respond: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Answers one request. Returns the response body and whether the database was changed.
+fn handle(request: &mut Request, db: &mut Database, tokens: &mut HashMap<String, String>) -> Result<(Value, bool), ApiError> {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let query = parse_query(query);
+    let segments: Vec<String> = path.trim_matches('/').split('/').map(percent_decode).collect();
+    let segments: Vec<&str> = segments.iter().map(|s| s.as_str()).collect();
+    let method = request.method().clone();
+
+    if method == Method::Post && segments == ["login"] {
+        let login: LoginRequest = read_json(request)?;
+        let user_id = authenticate(db, &login.username, &login.password).map_err(|e| (401, e))?;
+        let token = uuid::Uuid::new_v4().to_string();
+        tokens.insert(token.clone(), user_id.clone());
+        return Ok((json!({ "token": token, "user_id": user_id }), false));
+    }
+
+    let token = request.headers().iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+        .unwrap_or_default();
+    let user_id = match tokens.get(&token) {
+        Some(user_id) if db.user_by_id(user_id).is_some() => user_id.clone(),
+        _ => return Err((401, "Log in at POST /login and send the token as 'Authorization: Bearer <token>'.".to_string())),
+    };
+    let date = || query.get("date").cloned().unwrap_or_else(services::today);
+
+    let (body, changed) = match (&method, segments.as_slice()) {
+        (Method::Post, ["logout"]) => {
+            tokens.remove(&token);
+            (json!({}), false)
+        }
+        (Method::Get, ["users"]) => {
+            let mut users: Vec<Value> = db.users.values()
+                .map(|user| json!({ "user_id": user.user_id, "username": user.username }))
+                .collect();
+            users.sort_by_key(|user| user["username"].as_str().unwrap_or_default().to_string());
+            (Value::Array(users), false)
+        }
+        (Method::Get, ["users", "me"]) => {
+            let user = db.user_by_id(&user_id).unwrap();
+            (json!({
+                "user_id": user.user_id,
+                "username": user.username,
+                "profile": user.profile,
+                "target_calories": user.profile.calculate_target_calories(),
+            }), false)
+        }
+        (Method::Get, ["foods"]) => {
+            let keywords = query.get("keywords").map(|k| split_keywords(k)).unwrap_or_default();
+            let any = query.get("any").is_some_and(|any| any == "true");
+            (foods(db, &user_id, &keywords, any).json, false)
+        }
+        (Method::Post, ["foods"]) => {
+            let food: NewFoodRequest = read_json(request)?;
+            let food = new_basic_food(&user_id, &food.name, &food.calories.to_string(), food.keywords, food.brand, food.private)
+                .map_err(bad_request)?;
+            let id = food.id.clone();
+            db.save_basic_food(food);
+            (json!({ "id": id }), true)
+        }
+        (Method::Get, ["foods", id @ ..]) => {
+            let id = id.join("/");
+            let food = match (db.basic_foods.get(&id), db.composite_foods.get(&id)) {
+                (Some(food), _) => serde_json::to_value(food).unwrap(),
+                (_, Some(food)) => serde_json::to_value(food).unwrap(),
+                _ => Value::Null,
+            };
+            if food.is_null() || !db.food_visible_to(&id, &user_id) {
+                return Err(not_found(&id));
+            }
+            (with_calories(db, &id, food), false)
+        }
+        (Method::Get, ["composite-foods"]) => {
+            let mut foods: Vec<&CompositeFood> = db.composite_foods.values()
+                .filter(|food| db.food_visible_to(&food.id, &user_id))
+                .collect();
+            foods.sort_by(|a, b| (&a.name, &a.id).cmp(&(&b.name, &b.id)));
+            let foods = foods.into_iter()
+                .map(|food| with_calories(db, &food.id, serde_json::to_value(food).unwrap()))
+                .collect();
+            (Value::Array(foods), false)
+        }
+        (Method::Get, ["composite-foods", id @ ..]) => {
+            let id = id.join("/");
+            match db.composite_foods.get(&id) {
+                Some(food) if db.food_visible_to(&id, &user_id) => {
+                    (with_calories(db, &id, serde_json::to_value(food).unwrap()), false)
+                }
+                _ => return Err(not_found(&id)),
+            }
+        }
+        (Method::Get, ["log"]) => (list(db, &user_id, &date()).json, false),
+        (Method::Post, ["log"]) => {
+            let request: LogRequest = read_json(request)?;
+            let date = request.date.unwrap_or_else(date);
+            let mut entry = match (request.food, request.label) {
+                (Some(food), _) => {
+                    let food_id = resolve_food(db, &user_id, &food).map_err(bad_request)?;
+                    FoodLogEntry::new(&user_id, &date, &food_id, request.servings.unwrap_or(1.0))
+                }
+                (None, Some(label)) => {
+                    let calories = request.calories.ok_or_else(|| bad_request("Calories are required.".to_string()))?;
+                    FoodLogEntry::quick(&user_id, &date, &label, calories, None)
+                }
+                (None, None) => return Err(bad_request("Give either a food or a label.".to_string())),
+            };
+            place_entry(&mut entry, request.meal, request.time).map_err(bad_request)?;
+            let id = log_entry(db, entry).map_err(bad_request)?;
+            (json!({ "id": id }), true)
+        }
+        (Method::Put, ["log", entry_id]) => {
+            let edit: EditRequest = read_json(request)?;
+            if db.find_log_entry(&user_id, entry_id).is_none() {
+                return Err(not_found(entry_id));
+            }
+            edit_entry(db, &user_id, entry_id, edit.servings, edit.meal, edit.time).map_err(bad_request)?;
+            (json!({ "id": entry_id }), true)
+        }
+        (Method::Delete, ["log", entry_id]) => {
+            db.delete_log_entry(&user_id, entry_id).ok_or_else(|| not_found(entry_id))?;
+            (json!({ "id": entry_id }), true)
+        }
+        (Method::Get, ["summary"]) => (summary(db, &user_id, &date()).json, false),
+        _ => return Err((404, format!("No route for {} {}.", method, path))),
+    };
+    Ok((body, changed))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(request: &mut Request) -> Result<T, ApiError> {
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body).map_err(|e| bad_request(e.to_string()))?;
+    serde_json::from_str(&body).map_err(|e| bad_request(format!("Invalid request body: {}", e)))
+}
+
+/// Adds the calories per serving, which foods only store for basic foods.
+fn with_calories(db: &Database, food_id: &str, mut food: Value) -> Value {
+    food["calories_per_serving"] = json!(db.get_food_calories(food_id).unwrap_or(0.0));
+    food
+}
+
+fn bad_request(error: String) -> ApiError {
+    (400, error)
+}
+
+fn not_found(id: &str) -> ApiError {
+    (404, format!("'{}' does not exist.", id))
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+/// Decodes `%XX` escapes and `+` as a space. Invalid escapes are kept as they are.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}